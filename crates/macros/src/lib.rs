@@ -0,0 +1,438 @@
+//! Derives for `arte-protocol`'s serialization traits, re-exported from there.
+//!
+//! Fields are written and read in declaration order with their own `AsyncSerialize` and
+//! `AsyncDeserialize` impls, unless marked with one of these attributes:
+//!
+//! - `#[varint]` sends an integer as a `VarInt`
+//! - `#[limit(n)]` rejects strings longer than `n`, both when reading and writing
+//! - `#[json]` sends the value as a JSON string, and may be combined with `#[limit(n)]`
+//! - `#[rest]` takes up everything up to the end of the packet, for `Bytes`
+//!
+//! On an `Option` field these apply to the value, after the usual `bool` prefix.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Expr, Fields, GenericArgument, Ident,
+    LitInt, Member, PathArguments, Type,
+};
+
+/// Implements `Packet`, `AsyncSerialize` and `AsyncDeserializeContexful` with
+/// `PacketContext`, checking the id and state before reading any fields.
+///
+/// ```ignore
+/// #[derive(Packet)]
+/// #[packet(id = 0x00, state = Login)]
+/// pub struct LoginStart {
+///     #[limit(16)]
+///     pub username: String,
+///     pub uuid: Option<Uuid>,
+/// }
+/// ```
+#[proc_macro_derive(Packet, attributes(packet, varint, limit, json, rest))]
+pub fn derive_packet(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_packet(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `AsyncSerialize` by writing every field in order.
+#[proc_macro_derive(AsyncSerialize, attributes(varint, limit, json, rest))]
+pub fn derive_async_serialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_serialize(&input, Mode::Async)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `AsyncDeserialize` by reading every field in order.
+#[proc_macro_derive(AsyncDeserialize, attributes(varint, limit, json, rest))]
+pub fn derive_async_deserialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_deserialize(&input, Mode::Async)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Encode` by encoding every field in order.
+#[proc_macro_derive(Encode, attributes(varint, limit, json, rest))]
+pub fn derive_encode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_serialize(&input, Mode::Sync)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Decode` by decoding every field in order.
+#[proc_macro_derive(Decode, attributes(varint, limit, json, rest))]
+pub fn derive_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_deserialize(&input, Mode::Sync)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Whether to generate the `async` traits reading from streams, or the ones working on
+/// buffers in memory.
+#[derive(Clone, Copy)]
+enum Mode {
+    Async,
+    Sync,
+}
+
+impl Mode {
+    /// Writes `value`, a reference to something serializable.
+    fn write(self, value: TokenStream2) -> TokenStream2 {
+        match self {
+            Mode::Async => quote!(::arte_protocol::AsyncSerialize::write_to(#value, writer).await?),
+            Mode::Sync => quote!(::arte_protocol::Encode::encode(#value, buf)?),
+        }
+    }
+
+    fn read(self, ty: TokenStream2) -> TokenStream2 {
+        match self {
+            Mode::Async => {
+                quote!(<#ty as ::arte_protocol::AsyncDeserialize>::read_from(reader).await?)
+            }
+            Mode::Sync => quote!(<#ty as ::arte_protocol::Decode>::decode(buf)?),
+        }
+    }
+
+    /// Calls one of the `fundamental` helpers, named `read_*`/`write_*` or `decode_*`/`encode_*`.
+    fn helper(self, read: bool, name: &str, args: TokenStream2) -> TokenStream2 {
+        let (prefix, stream) = match (self, read) {
+            (Mode::Async, true) => ("read", quote!(reader)),
+            (Mode::Async, false) => ("write", quote!(writer)),
+            (Mode::Sync, true) => ("decode", quote!(buf)),
+            (Mode::Sync, false) => ("encode", quote!(buf)),
+        };
+        let function = Ident::new(&format!("{prefix}_{name}"), proc_macro2::Span::call_site());
+        let call = quote!(::arte_protocol::fundamental::#function(#stream, #args));
+
+        match self {
+            Mode::Async => quote!(#call.await?),
+            Mode::Sync => quote!(#call?),
+        }
+    }
+}
+
+fn expand_packet(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut id = None::<LitInt>;
+    let mut state = None::<Ident>;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("packet"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                id = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("state") {
+                state = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("expected `id` or `state`"));
+            }
+            Ok(())
+        })?;
+    }
+    let (Some(id), Some(state)) = (id, state) else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "expected #[packet(id = ..., state = ...)]",
+        ));
+    };
+
+    let serialize = expand_serialize(input, Mode::Async)?;
+    let encode = expand_serialize(input, Mode::Sync)?;
+    let read = read_fields(input, Mode::Async)?;
+    let decode = read_fields(input, Mode::Sync)?;
+
+    Ok(quote! {
+        impl #impl_generics ::arte_protocol::Packet for #name #ty_generics #where_clause {
+            const ID: ::arte_protocol::fundamental::VarInt<i32> =
+                ::arte_protocol::fundamental::VarInt(#id);
+            const STATE: ::arte_protocol::State = ::arte_protocol::State::#state;
+        }
+
+        #serialize
+
+        #encode
+
+        impl #impl_generics ::arte_protocol::AsyncDeserializeContexful for #name #ty_generics
+            #where_clause
+        {
+            type Context = ::arte_protocol::PacketContext;
+
+            async fn read_with_context(
+                reader: &mut impl ::arte_protocol::fundamental::ReadExt,
+                context: &Self::Context,
+            ) -> ::arte_protocol::Result<Self> {
+                <Self as ::arte_protocol::Packet>::check_context(context)?;
+
+                #read
+            }
+        }
+
+        impl #impl_generics ::arte_protocol::DecodeContextful for #name #ty_generics
+            #where_clause
+        {
+            type Context = ::arte_protocol::PacketContext;
+
+            fn decode_with_context(
+                buf: &mut impl ::arte_protocol::bytes::Buf,
+                context: &Self::Context,
+            ) -> ::arte_protocol::Result<Self> {
+                <Self as ::arte_protocol::Packet>::check_context(context)?;
+
+                #decode
+            }
+        }
+    })
+}
+
+fn expand_serialize(input: &DeriveInput, mode: Mode) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let writes = fields(input)?
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            let write = field.kind.write(&field.ty, quote!(&self.#member), mode)?;
+            Ok(quote!(#write;))
+        })
+        .collect::<syn::Result<TokenStream2>>()?;
+
+    Ok(match mode {
+        Mode::Async => quote! {
+            impl #impl_generics ::arte_protocol::AsyncSerialize for #name #ty_generics
+                #where_clause
+            {
+                async fn write_to(
+                    &self,
+                    writer: &mut impl ::arte_protocol::fundamental::WriteExt,
+                ) -> ::arte_protocol::Result<()> {
+                    #writes
+                    Ok(())
+                }
+            }
+        },
+        Mode::Sync => quote! {
+            impl #impl_generics ::arte_protocol::Encode for #name #ty_generics #where_clause {
+                fn encode(
+                    &self,
+                    buf: &mut impl ::arte_protocol::bytes::BufMut,
+                ) -> ::arte_protocol::Result<()> {
+                    #writes
+                    Ok(())
+                }
+            }
+        },
+    })
+}
+
+fn expand_deserialize(input: &DeriveInput, mode: Mode) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let read = read_fields(input, mode)?;
+
+    Ok(match mode {
+        Mode::Async => quote! {
+            impl #impl_generics ::arte_protocol::AsyncDeserialize for #name #ty_generics
+                #where_clause
+            {
+                async fn read_from(
+                    reader: &mut impl ::arte_protocol::fundamental::ReadExt,
+                ) -> ::arte_protocol::Result<Self> {
+                    #read
+                }
+            }
+        },
+        Mode::Sync => quote! {
+            impl #impl_generics ::arte_protocol::Decode for #name #ty_generics #where_clause {
+                fn decode(
+                    buf: &mut impl ::arte_protocol::bytes::Buf,
+                ) -> ::arte_protocol::Result<Self> {
+                    #read
+                }
+            }
+        },
+    })
+}
+
+/// `Ok(Self { .. })` with every field read in order.
+fn read_fields(input: &DeriveInput, mode: Mode) -> syn::Result<TokenStream2> {
+    let fields = fields(input)?;
+
+    let reads = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            let read = field.kind.read(&field.ty, mode)?;
+            Ok(quote!(#member: #read,))
+        })
+        .collect::<syn::Result<TokenStream2>>()?;
+
+    Ok(quote!(Ok(Self { #reads })))
+}
+
+struct Field {
+    member: Member,
+    ty: Type,
+    kind: Kind,
+}
+
+fn fields(input: &DeriveInput) -> syn::Result<Vec<Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "only structs can be derived",
+        ));
+    };
+
+    let fields = match &data.fields {
+        Fields::Named(fields) => &fields.named,
+        Fields::Unnamed(fields) => &fields.unnamed,
+        Fields::Unit => return Ok(vec![]),
+    };
+
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            Ok(Field {
+                member: field
+                    .ident
+                    .clone()
+                    .map(Member::Named)
+                    .unwrap_or_else(|| Member::Unnamed(index.into())),
+                ty: field.ty.clone(),
+                kind: Kind::from_attrs(field)?,
+            })
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Kind {
+    varint: bool,
+    limit: Option<Expr>,
+    json: bool,
+    rest: bool,
+}
+
+impl Kind {
+    fn from_attrs(field: &syn::Field) -> syn::Result<Self> {
+        let mut kind = Self::default();
+
+        for attr in &field.attrs {
+            if attr.path().is_ident("varint") {
+                attr.meta.require_path_only()?;
+                kind.varint = true;
+            } else if attr.path().is_ident("limit") {
+                kind.limit = Some(attr.parse_args()?);
+            } else if attr.path().is_ident("json") {
+                attr.meta.require_path_only()?;
+                kind.json = true;
+            } else if attr.path().is_ident("rest") {
+                attr.meta.require_path_only()?;
+                kind.rest = true;
+            }
+        }
+
+        let exclusive = [kind.varint, kind.json || kind.limit.is_some(), kind.rest];
+        if exclusive.into_iter().filter(|set| *set).count() > 1 {
+            return Err(syn::Error::new(
+                field.span(),
+                "#[varint], #[rest] and #[limit]/#[json] can't be combined",
+            ));
+        }
+
+        Ok(kind)
+    }
+
+    fn is_plain(&self) -> bool {
+        !self.varint && self.limit.is_none() && !self.json && !self.rest
+    }
+
+    /// Writes `value`, a reference to a `ty`.
+    fn write(&self, ty: &Type, value: TokenStream2, mode: Mode) -> syn::Result<TokenStream2> {
+        if !self.is_plain() {
+            if let Some(inner) = option_inner(ty) {
+                let is_some = mode.write(quote!(&(#value).is_some()));
+                let write = self.write(inner, quote!(value), mode)?;
+                return Ok(quote! {
+                    #is_some;
+                    if let Some(value) = #value {
+                        #write;
+                    }
+                });
+            }
+        }
+
+        Ok(match (&self.limit, self.json) {
+            _ if self.varint => mode.write(quote!(&::arte_protocol::fundamental::VarInt(*#value))),
+            _ if self.rest => mode.helper(false, "rest", value),
+            (Some(limit), true) => mode.helper(false, "json_limit", quote!(#value, #limit)),
+            (None, true) => mode.write(quote!(&::arte_protocol::fundamental::Json(#value))),
+            (Some(limit), false) => mode.helper(false, "string_limit", quote!(#value, #limit)),
+            (None, false) => mode.write(value),
+        })
+    }
+
+    /// Reads a `ty`.
+    fn read(&self, ty: &Type, mode: Mode) -> syn::Result<TokenStream2> {
+        if !self.is_plain() {
+            if let Some(inner) = option_inner(ty) {
+                let is_some = mode.read(quote!(bool));
+                let read = self.read(inner, mode)?;
+                return Ok(quote! {
+                    if #is_some {
+                        Some(#read)
+                    } else {
+                        None
+                    }
+                });
+            }
+        }
+
+        Ok(match (&self.limit, self.json) {
+            _ if self.varint => {
+                let read = mode.read(quote!(::arte_protocol::fundamental::VarInt<#ty>));
+                quote!(#read.0)
+            }
+            _ if self.rest => mode.helper(true, "rest", quote!()),
+            (Some(limit), true) => mode.helper(true, "json_limit", quote!(#limit)),
+            (None, true) => {
+                let read = mode.read(quote!(::arte_protocol::fundamental::Json<#ty>));
+                quote!(#read.0)
+            }
+            (Some(limit), false) => mode.helper(true, "string_limit", quote!(#limit)),
+            (None, false) => mode.read(quote!(#ty)),
+        })
+    }
+}
+
+/// `T` if `ty` is spelled `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,71 @@
+//! Compares the `async` stream codec with the in-memory one on the same data.
+
+use arte_protocol::{
+    fundamental::{ReadExt, VarInt},
+    phases::login::Property,
+    *,
+};
+use async_std::task;
+use bytes::{Bytes, BytesMut};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn varints() -> Vec<VarInt> {
+    (0..1024).map(|i| VarInt(i * 4099 - 2_000_000)).collect()
+}
+
+fn strings() -> Vec<String> {
+    (0..256).map(|i| format!("player-{i}").repeat(8)).collect()
+}
+
+fn properties() -> Vec<Property> {
+    (0..8)
+        .map(|_| Property {
+            name: String::from("textures"),
+            value: "e30=".repeat(128),
+            signature: Some("c2lnbmF0dXJl".repeat(64)),
+        })
+        .collect()
+}
+
+fn bench<T>(c: &mut Criterion, name: &str, value: T)
+where
+    T: AsyncSerialize + AsyncDeserialize + Encode + Decode,
+{
+    let mut data = BytesMut::new();
+    value.encode(&mut data).unwrap();
+    let data = data.freeze();
+
+    let mut group = c.benchmark_group(name);
+
+    group.bench_function("async encode", |b| {
+        b.iter(|| {
+            let mut buf = vec![];
+            task::block_on(black_box(&value).write_to(&mut buf)).unwrap();
+            buf
+        })
+    });
+    group.bench_function("sync encode", |b| {
+        b.iter(|| {
+            let mut buf = BytesMut::new();
+            black_box(&value).encode(&mut buf).unwrap();
+            buf
+        })
+    });
+    group.bench_function("async decode", |b| {
+        b.iter(|| task::block_on((&data[..]).deserialize::<T>()).unwrap())
+    });
+    group.bench_function("sync decode", |b| {
+        b.iter(|| T::decode(&mut Bytes::clone(black_box(&data))).unwrap())
+    });
+
+    group.finish();
+}
+
+fn codec(c: &mut Criterion) {
+    bench(c, "varints", varints());
+    bench(c, "strings", strings());
+    bench(c, "properties", properties());
+}
+
+criterion_group!(benches, codec);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+#![no_main]
+
+use arte_protocol::{
+    fundamental::ReadExt,
+    phases::handshaking::{ForwardedHandshake, Handshake},
+    DecodeContextful, PacketContext, Packet, State,
+};
+use async_std::task;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let context = PacketContext {
+        id: Handshake::ID,
+        state: State::Handshaking,
+    };
+
+    let _ = Handshake::decode_with_context(&mut &data[..], &context);
+    let _ = ForwardedHandshake::decode_with_context(&mut &data[..], &context);
+    let _ = task::block_on((&data[..]).deserialize_with_context::<Handshake>(&context));
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use arte_protocol::{
+    fundamental::ReadExt,
+    phases::login::{LoginStart, ServerboundLogin},
+    DecodeContextful, PacketContext, Packet, State,
+};
+use async_std::task;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let context = PacketContext {
+        id: LoginStart::ID,
+        state: State::Login,
+    };
+
+    let _ = ServerboundLogin::decode_with_context(&mut &data[..], &context);
+    let _ = task::block_on((&data[..]).deserialize_with_context::<LoginStart>(&context));
+});
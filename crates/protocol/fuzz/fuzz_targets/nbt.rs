@@ -0,0 +1,29 @@
+#![no_main]
+
+use arte_protocol::nbt::{self, Compound, NbtLimits, Tag};
+use async_std::task;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let limits = NbtLimits::default();
+
+    let mut buf = data;
+    let decoded = Compound::decode_named(&mut buf, &limits);
+    let walked = task::block_on(nbt::read_root_bytes(&mut &data[..], true, &limits));
+
+    // both readers agree on where the root ends, and what decodes encodes back the same
+    if let (Ok((name, root)), Ok(walked)) = (decoded, walked) {
+        assert_eq!(walked.len(), data.len() - buf.len());
+
+        let mut encoded = vec![];
+        root.encode_named(&name, &mut encoded).unwrap();
+        let (_, again) = Compound::decode_named(&mut &encoded[..], &limits).unwrap();
+        let mut reencoded = vec![];
+        again.encode_named(&name, &mut reencoded).unwrap();
+        assert_eq!(encoded, reencoded);
+    }
+
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = text.parse::<Tag>();
+    }
+});
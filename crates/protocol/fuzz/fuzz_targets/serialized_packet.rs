@@ -0,0 +1,16 @@
+#![no_main]
+
+use arte_protocol::fundamental::{FrameLimits, SerializedPacket};
+use async_std::task;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // the first byte picks whether compression is enabled, and with which threshold
+    let Some((&mode, mut frame)) = data.split_first() else {
+        return;
+    };
+    let threshold = (mode & 1 == 1).then_some(usize::from(mode >> 1) * 4);
+
+    let limits = FrameLimits::default();
+    let _ = task::block_on(SerializedPacket::read_from(&mut frame, threshold, &limits));
+});
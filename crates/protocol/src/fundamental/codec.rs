@@ -0,0 +1,314 @@
+use std::cell::Cell;
+
+use super::*;
+
+/// Fails with `UnexpectedEof` unless `buf` has at least `length` more bytes, since [`Buf`]'s
+/// getters panic instead.
+pub fn ensure_remaining(buf: &impl Buf, length: usize) -> Result<()> {
+    if buf.remaining() < length {
+        return Err(Error::Io(std::io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "Unexpected end of packet",
+        )));
+    }
+
+    Ok(())
+}
+
+thread_local! {
+    static BUDGET: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Runs `decode` with at most `budget` bytes to spend on the collections and strings it
+/// decodes, which bounds what a small frame full of length prefixes can make us allocate.
+/// What is left of it is put back into `budget` after.
+///
+/// Decoding is synchronous, so the budget can't leak into another connection's frame. The
+/// async readers can't be bounded this way and cap collections at [`MAX_COLLECTION_LENGTH`]
+/// instead.
+pub fn with_decode_budget<R>(budget: &mut usize, decode: impl FnOnce() -> R) -> R {
+    struct Restore(Option<usize>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            BUDGET.set(self.0);
+        }
+    }
+
+    let _restore = Restore(BUDGET.replace(Some(*budget)));
+    let result = decode();
+    *budget = BUDGET.get().unwrap_or_default();
+    result
+}
+
+/// Takes `bytes` out of the budget set by [`with_decode_budget`], if any.
+pub fn spend_decode_budget(bytes: usize) -> Result<()> {
+    BUDGET.with(|budget| match budget.get() {
+        Some(remaining) if bytes > remaining => Err(Error::DecodeBudgetExceeded {
+            needed: bytes,
+            remaining,
+        }),
+        Some(remaining) => {
+            budget.set(Some(remaining - bytes));
+            Ok(())
+        }
+        None => Ok(()),
+    })
+}
+
+/// Reads a collection's length prefix, which can't be more than there are bytes left since
+/// every item takes at least one.
+fn decode_collection_length<T>(buf: &mut impl Buf) -> Result<usize> {
+    let length = VarInt::<i32>::decode(buf)?.try_usize()?;
+
+    if length > buf.remaining() {
+        return Err(Error::CollectionTooLarge {
+            length,
+            max: buf.remaining(),
+        });
+    }
+    spend_decode_budget(length.saturating_mul(size_of::<T>()))?;
+
+    Ok(length)
+}
+
+/// Decodes a string of at most `limit` UTF-16 code units.
+pub fn decode_string_limit(buf: &mut impl Buf, limit: usize) -> Result<String> {
+    let length = VarInt::<i32>::decode(buf)?.try_usize()?;
+    check_string_bytes(length, limit)?;
+    ensure_remaining(buf, length)?;
+    spend_decode_budget(length)?;
+
+    let mut bytes = vec![0; length];
+    buf.copy_to_slice(&mut bytes);
+
+    string_from_utf8(bytes, limit)
+}
+
+/// Decodes a JSON string of at most `limit` UTF-16 code units.
+pub fn decode_json_limit<T: serde::de::DeserializeOwned>(
+    buf: &mut impl Buf,
+    limit: usize,
+) -> Result<T> {
+    Ok(serde_json::from_str(&decode_string_limit(buf, limit)?)?)
+}
+
+/// Puts a string, failing if it is longer than `limit` UTF-16 code units.
+pub fn encode_string_limit(buf: &mut impl BufMut, string: &str, limit: usize) -> Result<()> {
+    check_string_limit(string, limit)?;
+
+    VarInt::<i32>::usize(string.len()).encode(buf)?;
+    buf.put_slice(string.as_bytes());
+    Ok(())
+}
+
+/// Puts `value` as a JSON string, failing if it is longer than `limit` UTF-16 code units.
+pub fn encode_json_limit<T: serde::Serialize>(
+    buf: &mut impl BufMut,
+    value: &T,
+    limit: usize,
+) -> Result<()> {
+    encode_string_limit(buf, &serde_json::to_string(value)?, limit)
+}
+
+/// Takes everything up to the end of the packet.
+pub fn decode_rest(buf: &mut impl Buf) -> Result<Bytes> {
+    Ok(buf.copy_to_bytes(buf.remaining()))
+}
+
+/// Puts `data` without a length prefix, so it must be the last field of the packet.
+pub fn encode_rest(buf: &mut impl BufMut, data: &[u8]) -> Result<()> {
+    buf.put_slice(data);
+    Ok(())
+}
+
+macro_rules! impl_int_codec {
+    ($($int:ty),*$(,)?) => {
+        $(
+        impl Encode for $int {
+            fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+                buf.put_slice(&self.to_be_bytes());
+                Ok(())
+            }
+        }
+
+        impl Decode for $int {
+            fn decode(buf: &mut impl Buf) -> Result<Self> {
+                let mut bytes = [0; size_of::<Self>()];
+
+                ensure_remaining(buf, bytes.len())?;
+                buf.copy_to_slice(&mut bytes);
+
+                Ok(Self::from_be_bytes(bytes))
+            }
+        }
+        )*
+    };
+}
+
+impl_int_codec![u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, f32, f64];
+
+impl Encode for bool {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        (*self as u8).encode(buf)
+    }
+}
+
+impl Decode for bool {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Ok(read_enum!([u8::decode(buf)?] 0x00 => false, 0x01 => true))
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        VarInt::<i32>::usize(self.len()).encode(buf)?;
+        buf.put_slice(self.as_bytes());
+        Ok(())
+    }
+}
+
+impl Decode for String {
+    /// Decodes a string of at most [`MAX_STRING_LENGTH`], use [`decode_string_limit`] for
+    /// others.
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        decode_string_limit(buf, MAX_STRING_LENGTH)
+    }
+}
+
+impl<const N: usize> Encode for StringLimit<N> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        check_string_limit(&self.0, N)?;
+
+        self.0.encode(buf)
+    }
+}
+
+impl<const N: usize> Decode for StringLimit<N> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        decode_string_limit(buf, N).map(Self)
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for BoundedVec<T, N> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        Self::check_length(self.0.len())?;
+
+        self.0.encode(buf)
+    }
+}
+
+impl<T: Decode, const N: usize> Decode for BoundedVec<T, N> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        let length = decode_collection_length::<T>(buf)?;
+        Self::check_length(length)?;
+
+        let mut result = Vec::with_capacity(length);
+        for _ in 0..length {
+            result.push(T::decode(buf)?);
+        }
+
+        Ok(Self(result))
+    }
+}
+
+impl Encode for Uuid {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        buf.put_slice(self.as_bytes());
+        Ok(())
+    }
+}
+
+impl Decode for Uuid {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        let mut bytes = [0; 16];
+
+        ensure_remaining(buf, bytes.len())?;
+        buf.copy_to_slice(&mut bytes);
+
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.is_some().encode(buf)?;
+        if let Some(value) = self {
+            value.encode(buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Ok(if bool::decode(buf)? {
+            Some(T::decode(buf)?)
+        } else {
+            None
+        })
+    }
+}
+
+impl<T: DecodeContextful> DecodeContextful for Option<T> {
+    type Context = T::Context;
+
+    fn decode_with_context(buf: &mut impl Buf, context: &Self::Context) -> Result<Self> {
+        Ok(if bool::decode(buf)? {
+            Some(T::decode_with_context(buf, context)?)
+        } else {
+            None
+        })
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        VarInt::<i32>::usize(self.len()).encode(buf)?;
+
+        for item in self {
+            item.encode(buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        let length = decode_collection_length::<T>(buf)?;
+        let mut result = Vec::with_capacity(length);
+
+        for _ in 0..length {
+            result.push(T::decode(buf)?);
+        }
+
+        Ok(result)
+    }
+}
+
+impl<T: DecodeContextful> DecodeContextful for Vec<T> {
+    type Context = T::Context;
+
+    fn decode_with_context(buf: &mut impl Buf, context: &Self::Context) -> Result<Self> {
+        let length = decode_collection_length::<T>(buf)?;
+        let mut result = Vec::with_capacity(length);
+
+        for _ in 0..length {
+            result.push(T::decode_with_context(buf, context)?);
+        }
+
+        Ok(result)
+    }
+}
+
+impl<T: serde::Serialize> Encode for Json<T> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        serde_json::to_string(&self.0)?.encode(buf)
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> Decode for Json<T> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Ok(Self(serde_json::from_str(&String::decode(buf)?)?))
+    }
+}
@@ -1,19 +1,29 @@
 use super::*;
 
 use async_compression::futures::{bufread::ZlibDecoder, write::ZlibEncoder};
+use flate2::Compression;
+use futures::AsyncWriteExt;
+use std::io::{Read as _, Write as _};
 
 pub struct Zlib<T>(pub T);
 
 impl<T: AsyncSerialize> AsyncSerialize for Zlib<T> {
     async fn write_to(&self, writer: &mut impl super::WriteExt) -> crate::Result<()> {
-        self.0.write_to(&mut ZlibEncoder::new(writer)).await
+        // closing the encoder is what writes out the end of the stream, but it also closes
+        // the underlying writer, so compress into a buffer first
+        let mut encoder = ZlibEncoder::new(vec![]);
+        self.0.write_to(&mut encoder).await?;
+        encoder.close().await?;
+
+        Ok(async_std::io::WriteExt::write_all(writer, &encoder.into_inner()).await?)
     }
 }
 
 impl<T: AsyncDeserialize> AsyncDeserialize for Zlib<T> {
+    /// Fails once `T` reads more than [`FrameLimits::MAX_DATA_LENGTH`] inflated bytes.
     async fn read_from(reader: &mut impl super::ReadExt) -> crate::Result<Self> {
         Ok(Self(
-            T::read_from(&mut ZlibDecoder::new(BufReader::new(reader))).await?,
+            T::read_from(&mut ZlibDecoder::new(BufReader::new(reader)).take(MAX_INFLATED)).await?,
         ))
     }
 }
@@ -23,7 +33,58 @@ impl<T: AsyncDeserializeContexful> AsyncDeserializeContexful for Zlib<T> {
 
     async fn read_with_context(reader: &mut impl ReadExt, context: &Self::Context) -> Result<Self> {
         Ok(Self(
-            T::read_with_context(&mut ZlibDecoder::new(BufReader::new(reader)), context).await?,
+            T::read_with_context(
+                &mut ZlibDecoder::new(BufReader::new(reader)).take(MAX_INFLATED),
+                context,
+            )
+            .await?,
         ))
     }
 }
+
+impl<T: Encode> Encode for Zlib<T> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        let mut data = BytesMut::new();
+        self.0.encode(&mut data)?;
+
+        let mut encoder = flate2::write::ZlibEncoder::new(buf.writer(), Compression::default());
+        encoder.write_all(&data)?;
+        encoder.finish()?;
+        Ok(())
+    }
+}
+
+/// Without a frame to say how much to expect, nothing may inflate to more than a packet may.
+const MAX_INFLATED: u64 = FrameLimits::MAX_DATA_LENGTH as u64;
+
+/// Inflates everything left in `buf`, failing rather than inflating more than a packet may.
+fn inflate(buf: &mut impl Buf) -> Result<Bytes> {
+    // inflating one byte more than allowed is enough to tell it's too much
+    let mut data = vec![];
+    flate2::read::ZlibDecoder::new(buf.reader())
+        .take(MAX_INFLATED + 1)
+        .read_to_end(&mut data)?;
+
+    if data.len() as u64 > MAX_INFLATED {
+        return Err(Error::DataTooLarge {
+            length: data.len(),
+            max: FrameLimits::MAX_DATA_LENGTH,
+        });
+    }
+
+    Ok(data.into())
+}
+
+impl<T: Decode> Decode for Zlib<T> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Ok(Self(T::decode(&mut inflate(buf)?)?))
+    }
+}
+
+impl<T: DecodeContextful> DecodeContextful for Zlib<T> {
+    type Context = T::Context;
+
+    fn decode_with_context(buf: &mut impl Buf, context: &Self::Context) -> Result<Self> {
+        Ok(Self(T::decode_with_context(&mut inflate(buf)?, context)?))
+    }
+}
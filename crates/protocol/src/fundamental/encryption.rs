@@ -0,0 +1,183 @@
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use aes::{
+    cipher::{inout::InOutBuf, BlockDecryptMut, BlockEncryptMut, KeyIvInit},
+    Aes128,
+};
+use rsa::{pkcs8::EncodePublicKey, Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
+
+use super::*;
+
+pub type Encryptor = cfb8::Encryptor<Aes128>;
+pub type Decryptor = cfb8::Decryptor<Aes128>;
+
+/// The RSA keypair a server in online mode uses to receive the shared secret.
+pub struct ServerKey {
+    private: RsaPrivateKey,
+    public_der: Vec<u8>,
+}
+
+impl ServerKey {
+    pub const BITS: usize = 1024;
+
+    pub fn generate() -> Result<Self> {
+        let private = RsaPrivateKey::new(&mut rand::thread_rng(), Self::BITS)?;
+        let public_der = RsaPublicKey::from(&private)
+            .to_public_key_der()
+            .map_err(|error| rsa::Error::from(rsa::pkcs8::Error::from(error)))?
+            .into_vec();
+
+        Ok(Self {
+            private,
+            public_der,
+        })
+    }
+
+    /// The public key in the ASN.1 DER form sent in Encryption Request.
+    pub fn public_key_der(&self) -> &[u8] {
+        &self.public_der
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.private.decrypt(Pkcs1v15Encrypt, data)?)
+    }
+}
+
+/// A stream that, once encryption is enabled, transparently runs everything read and
+/// written through AES-128 in CFB8 mode.
+pub struct CipherStream<S> {
+    inner: S,
+    encryptor: Option<Encryptor>,
+    decryptor: Option<Decryptor>,
+    // bytes that were already encrypted but not yet accepted by the inner stream
+    pending: Vec<u8>,
+}
+
+impl<S> CipherStream<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            encryptor: None,
+            decryptor: None,
+            pending: vec![],
+        }
+    }
+
+    /// Minecraft uses the shared secret as both the key and the initial vector.
+    pub fn enable_encryption(&mut self, shared_secret: &[u8; 16]) {
+        self.encryptor = Some(Encryptor::new(shared_secret.into(), shared_secret.into()));
+        self.decryptor = Some(Decryptor::new(shared_secret.into(), shared_secret.into()));
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encryptor.is_some()
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Write + Unpin> CipherStream<S> {
+    fn poll_write_pending(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        while !self.pending.is_empty() {
+            let written = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.pending))?;
+
+            if written == 0 {
+                return Poll::Ready(Err(std::io::Error::new(
+                    ErrorKind::WriteZero,
+                    "poll_write returned Ok(0)",
+                )));
+            }
+
+            self.pending.drain(..written);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Clones continue from the same position in the cipher, so afterwards only one of them
+/// may be read from and only one written to.
+impl<S: Clone> Clone for CipherStream<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            encryptor: self.encryptor.clone(),
+            decryptor: self.decryptor.clone(),
+            pending: vec![],
+        }
+    }
+}
+
+impl<S: Read + Unpin> Read for CipherStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let read = ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+
+        if let Some(decryptor) = &mut this.decryptor {
+            let (blocks, _) = InOutBuf::from(&mut buf[..read]).into_chunks();
+            decryptor.decrypt_blocks_inout_mut(blocks);
+        }
+
+        Poll::Ready(Ok(read))
+    }
+}
+
+impl<S: Write + Unpin> Write for CipherStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+
+        if this.encryptor.is_none() {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        }
+
+        // the cipher can't be rewound, so everything it encrypts has to be sent eventually
+        ready!(this.poll_write_pending(cx))?;
+
+        let start = this.pending.len();
+        this.pending.extend_from_slice(buf);
+        if let Some(encryptor) = &mut this.encryptor {
+            let (blocks, _) = InOutBuf::from(&mut this.pending[start..]).into_chunks();
+            encryptor.encrypt_blocks_inout_mut(blocks);
+        }
+
+        // whatever doesn't fit now is written on the next write or flush
+        if let Poll::Ready(Err(error)) = this.poll_write_pending(cx) {
+            return Poll::Ready(Err(error));
+        }
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_pending(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_pending(cx))?;
+        Pin::new(&mut this.inner).poll_close(cx)
+    }
+}
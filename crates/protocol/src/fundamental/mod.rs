@@ -2,13 +2,18 @@ use std::{io::ErrorKind, pin::Pin};
 
 use super::*;
 
+mod codec;
 mod compression;
+mod encryption;
 mod packets;
+mod primitives;
 mod varint;
 
-use async_std::io::WriteExt as _;
+pub use codec::*;
 pub use compression::*;
+pub use encryption::*;
 pub use packets::*;
+pub use primitives::*;
 pub use varint::*;
 
 pub trait ReadExt: async_std::io::ReadExt + Read + Unpin {
@@ -85,21 +90,101 @@ pub trait WriteExt: Write + Unpin {
 
 impl<T: Write + Unpin> WriteExt for T {}
 
-pub async fn read_string_limit(reader: &mut impl ReadExt, limit: usize) -> Result<String> {
-    let length = VarInt::<i32>::read_from(reader)
-        .await?
-        .0
-        .try_into_into::<usize>()
-        .unwrap();
+/// The longest string the protocol allows anywhere.
+pub const MAX_STRING_LENGTH: usize = 32767;
+/// The longest JSON chat component the protocol allows.
+pub const MAX_CHAT_LENGTH: usize = 262144;
+/// The most items a `Vec` read from an async reader may claim.
+///
+/// Unlike a decoded frame, a reader can't tell how much is left in it, but every item takes
+/// at least a byte, so no more fit into the largest frame.
+pub const MAX_COLLECTION_LENGTH: usize = FrameLimits::MAX_FRAME_LENGTH;
+
+/// Fails unless `string` is at most `limit` UTF-16 code units long, which is how the
+/// protocol counts.
+pub fn check_string_limit(string: &str, limit: usize) -> Result<()> {
+    // every code unit takes at least a byte
+    if string.len() <= limit {
+        return Ok(());
+    }
+
+    let length = string.encode_utf16().count();
+    if length > limit {
+        return Err(Error::StringTooLarge { length, limit });
+    }
+
+    Ok(())
+}
 
+/// Checks the byte length prefix of a string limited to `limit` UTF-16 code units, each of
+/// which takes at most 3 bytes.
+fn check_string_bytes(length: usize, limit: usize) -> Result<()> {
+    let limit = limit.saturating_mul(3);
     if length > limit {
         return Err(Error::StringTooLarge { length, limit });
     }
 
-    let mut buf = String::new();
-    reader.take(length as u64).read_to_string(&mut buf).await?;
+    Ok(())
+}
+
+fn string_from_utf8(bytes: Vec<u8>, limit: usize) -> Result<String> {
+    let string = String::from_utf8(bytes)
+        .map_err(|error| Error::Io(std::io::Error::new(ErrorKind::InvalidData, error)))?;
+    check_string_limit(&string, limit)?;
+
+    Ok(string)
+}
+
+/// Reads a string of at most `limit` UTF-16 code units.
+pub async fn read_string_limit(reader: &mut impl ReadExt, limit: usize) -> Result<String> {
+    let length = VarInt::<i32>::read_from(reader).await?.try_usize()?;
+    check_string_bytes(length, limit)?;
+
+    let mut bytes = vec![0; length];
+    reader.read_exact(&mut bytes).await?;
 
-    Ok(buf)
+    string_from_utf8(bytes, limit)
+}
+
+/// Reads a JSON string of at most `limit` UTF-16 code units.
+pub async fn read_json_limit<T: serde::de::DeserializeOwned>(
+    reader: &mut impl ReadExt,
+    limit: usize,
+) -> Result<T> {
+    Ok(serde_json::from_str(
+        &read_string_limit(reader, limit).await?,
+    )?)
+}
+
+/// Writes a string, failing if it is longer than `limit` UTF-16 code units.
+pub async fn write_string_limit(
+    writer: &mut impl WriteExt,
+    string: &str,
+    limit: usize,
+) -> Result<()> {
+    check_string_limit(string, limit)?;
+
+    VarInt::<i32>::usize(string.len()).write_to(writer).await?;
+    Ok(writer.write_all(string.as_bytes()).await?)
+}
+
+/// Writes `value` as a JSON string, failing if it is longer than `limit` UTF-16 code units.
+pub async fn write_json_limit<T: serde::Serialize>(
+    writer: &mut impl WriteExt,
+    value: &T,
+    limit: usize,
+) -> Result<()> {
+    write_string_limit(writer, &serde_json::to_string(value)?, limit).await
+}
+
+/// Reads everything up to the end of the packet.
+pub async fn read_rest(reader: &mut impl ReadExt) -> Result<Bytes> {
+    Ok(reader.collect().await?)
+}
+
+/// Writes `data` without a length prefix, so it must be the last field of the packet.
+pub async fn write_rest(writer: &mut impl WriteExt, data: &[u8]) -> Result<()> {
+    Ok(writer.write_all(data).await?)
 }
 
 macro_rules! impl_int_rw {
@@ -113,7 +198,7 @@ macro_rules! impl_int_rw {
 
         impl AsyncDeserialize for $int {
             async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
-                let mut buf = [0; Self::BITS as usize / 8];
+                let mut buf = [0; size_of::<Self>()];
 
                 reader.read_exact(&mut buf).await?;
 
@@ -124,7 +209,7 @@ macro_rules! impl_int_rw {
     };
 }
 
-impl_int_rw![u8, i8, u16, i16, u32, i32, u64, i64, usize, isize];
+impl_int_rw![u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, f32, f64];
 
 #[macro_export]
 macro_rules! read_enum {
@@ -140,19 +225,18 @@ impl AsyncSerialize for String {
     async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
         try {
             VarInt::<i32>::usize(self.len()).write_to(writer).await?;
-            writer.write_all(self.as_bytes()).await?;
+            writer
+                .write_all(self.as_bytes())
+                .await
+                .map_err(Error::from)?;
         }
     }
 }
 
 impl AsyncDeserialize for String {
+    /// Reads a string of at most [`MAX_STRING_LENGTH`], use [`read_string_limit`] for others.
     async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
-        try {
-            let mut buf = Self::new();
-            let length = reader.deserialize::<VarInt>().await?.to_usize();
-            reader.take(length as u64).read_to_string(&mut buf).await?;
-            buf
-        }
+        read_string_limit(reader, MAX_STRING_LENGTH).await
     }
 }
 
@@ -248,10 +332,22 @@ impl<T: AsyncSerialize> AsyncSerialize for Vec<T> {
     }
 }
 
+/// Reads a collection's length prefix, failing if it is more than `max`.
+async fn read_collection_length(reader: &mut impl ReadExt, max: usize) -> Result<usize> {
+    let length = VarInt::<i32>::read_from(reader).await?.try_usize()?;
+    if length > max {
+        return Err(Error::CollectionTooLarge { length, max });
+    }
+
+    Ok(length)
+}
+
 impl<T: AsyncDeserialize> AsyncDeserialize for Vec<T> {
+    /// Reads at most [`MAX_COLLECTION_LENGTH`] items, use [`BoundedVec`] for fewer.
     async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
         try {
-            let length = VarInt::<i32>::read_from(reader).await?.to_usize();
+            let length = read_collection_length(reader, MAX_COLLECTION_LENGTH).await?;
+            // grown as items actually arrive rather than trusting the length up front
             let mut result = vec![];
 
             for _ in 0..length {
@@ -268,7 +364,7 @@ impl<T: AsyncDeserializeContexful> AsyncDeserializeContexful for Vec<T> {
 
     async fn read_with_context(reader: &mut impl ReadExt, context: &Self::Context) -> Result<Self> {
         try {
-            let length = VarInt::<i32>::read_from(reader).await?.to_usize();
+            let length = read_collection_length(reader, MAX_COLLECTION_LENGTH).await?;
             let mut result = vec![];
 
             for _ in 0..length {
@@ -280,6 +376,52 @@ impl<T: AsyncDeserializeContexful> AsyncDeserializeContexful for Vec<T> {
     }
 }
 
+/// A `Vec` that is written with a length prefix of at most `N`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BoundedVec<T, const N: usize>(pub Vec<T>);
+
+impl<T, const N: usize> BoundedVec<T, N> {
+    /// Fails with [`Error::CollectionTooLarge`] unless `length` fits.
+    pub fn check_length(length: usize) -> Result<()> {
+        if length > N {
+            return Err(Error::CollectionTooLarge { length, max: N });
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> std::ops::Deref for BoundedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: AsyncSerialize, const N: usize> AsyncSerialize for BoundedVec<T, N> {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        Self::check_length(self.0.len())?;
+
+        self.0.write_to(writer).await
+    }
+}
+
+impl<T: AsyncDeserialize, const N: usize> AsyncDeserialize for BoundedVec<T, N> {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        try {
+            let length = read_collection_length(reader, N).await?;
+
+            let mut result = Vec::with_capacity(length);
+            for _ in 0..length {
+                result.push(reader.deserialize().await?);
+            }
+
+            Self(result)
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StringLimit<const N: usize>(pub String);
 
@@ -293,7 +435,7 @@ impl<const N: usize> std::ops::Deref for StringLimit<N> {
 
 impl<const N: usize> AsyncSerialize for StringLimit<N> {
     async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
-        debug_assert!(self.0.len() <= N);
+        check_string_limit(&self.0, N)?;
 
         self.0.write_to(writer).await
     }
@@ -1,84 +1,103 @@
-use async_std::io;
+use std::{io::Read as _, time::Instant};
+
+use async_std::io::{self, WriteExt as _};
 
 use super::*;
 
+/// A packet id followed by the packet's fields, without any framing.
+#[derive(Clone)]
 pub struct PacketData {
     pub packet_id: VarInt,
     pub data: Bytes,
 }
 
-impl AsyncDeserializeContexful for PacketData {
-    type Context = u64;
+impl PacketData {
+    /// Serializes `packet`'s fields after its id.
+    pub fn from_packet<T: Encode + Packet>(packet: &T) -> Result<Self> {
+        let mut data = BytesMut::new();
+        packet.encode(&mut data)?;
 
-    async fn read_with_context(reader: &mut impl ReadExt, length: &Self::Context) -> Result<Self> {
-        try {
-            let packet_id: VarInt<i32> = reader.deserialize().await?;
-            Self {
-                packet_id,
-                data: reader
-                    .take(*length - packet_id.length() as u64)
-                    .collect()
-                    .await?,
-            }
-        }
+        Ok(Self {
+            packet_id: T::ID,
+            data: data.freeze(),
+        })
+    }
+
+    /// Length of the packet id and data together, as it appears in the frame.
+    pub fn length(&self) -> usize {
+        self.packet_id.length() + self.data.len()
     }
 }
 
 impl AsyncSerialize for PacketData {
     async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
         try {
-            writer
-                .serialize(&VarInt::<i32>(
-                    (self.packet_id.length() + self.data.len())
-                        .try_into()
-                        .unwrap(),
-                ))
-                .await?;
             writer.serialize(&self.packet_id).await?;
-            io::copy(&self.data[..], writer).await?;
+            io::copy(&self.data[..], writer)
+                .await
+                .map_err(Error::from)?;
         }
     }
 }
 
+impl Encode for PacketData {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.packet_id.encode(buf)?;
+        buf.put_slice(&self.data);
+        Ok(())
+    }
+}
+
+impl Decode for PacketData {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Ok(Self {
+            packet_id: VarInt::decode(buf)?,
+            data: decode_rest(buf)?,
+        })
+    }
+}
+
 pub enum SerializedPacket {
+    /// A packet framed as `length, id, data`, used before compression is enabled.
     Uncompressed(PacketData),
+    /// A packet framed as `length, data length, id, data`, used after Set Compression.
+    ///
+    /// The id and data are only actually deflated when they reach the compression
+    /// threshold, otherwise the data length is `0` and they are sent as is.
     Compressed(Zlib<PacketData>),
 }
 
 impl SerializedPacket {
-    pub async fn read_packet<T: AsyncDeserializeContexful<Context = PacketContext>>(
+    /// How many times its compressed size a packet's buffer starts out at, typical packets
+    /// compress about this well.
+    const INFLATION_GUESS: usize = 4;
+
+    pub fn into_data(self) -> PacketData {
+        match self {
+            Self::Uncompressed(data) | Self::Compressed(Zlib(data)) => data,
+        }
+    }
+
+    /// Reads one frame and decodes it as a `T`.
+    ///
+    /// `compression_threshold` is only `Some` once compression is enabled.
+    pub async fn read_packet<T: DecodeContextful<Context = PacketContext>>(
         reader: &mut impl ReadExt,
-        compressing: bool,
+        compression_threshold: Option<usize>,
+        limits: &FrameLimits,
+        budget: &mut DecodeBudget,
         state: State,
     ) -> Result<T> {
-        if compressing {
-            let packet_length = VarInt::<i32>::read_from(reader).await?;
-            let VarInt(data_length) = VarInt::<i32>::read_from(reader).await?;
-
-            let id = VarInt::<i32>::read_from(reader).await?;
-            if data_length == 0 {
-                let data_length =
-                    packet_length.0.try_into_into::<u64>().unwrap() - id.length() as u64;
-
-                T::read_with_context(&mut reader.take(data_length), &PacketContext { id, state })
-                    .await
-            } else {
-                Ok(Zlib::<T>::read_with_context(
-                    &mut reader
-                        .take(data_length.try_into_into::<u64>().unwrap() - id.length() as u64),
-                    &PacketContext { id, state },
-                )
-                .await?
-                .0)
-            }
-        } else {
-            let length = VarInt::<i32>::read_from(reader).await?;
-            let id = VarInt::<i32>::read_from(reader).await?;
-
-            let data_length = length.0.try_into_into::<u64>().unwrap() - id.length() as u64;
+        // the whole frame is read up front, so a packet that doesn't consume all of its data
+        // can't desynchronize the stream
+        let PacketData {
+            packet_id: id,
+            mut data,
+        } = Self::read_from(reader, compression_threshold, limits)
+            .await?
+            .into_data();
 
-            T::read_with_context(&mut reader.take(data_length), &PacketContext { id, state }).await
-        }
+        budget.spend(|| T::decode_with_context(&mut data, &PacketContext { id, state }))
     }
 
     pub async fn write_to(
@@ -86,77 +105,210 @@ impl SerializedPacket {
         writer: &mut impl WriteExt,
         compression_threshold: Option<usize>,
     ) -> Result<()> {
-        try {
-            match self {
-                Self::Uncompressed(data) => data.write_to(writer).await?,
-                Self::Compressed(data) => {
-                    let data_len = data.0.packet_id.length() + data.0.data.len();
-                    let data_length = VarInt::<i32>(data_len.try_into().unwrap());
-
-                    if let Some(threshold) = compression_threshold {
-                        if data_len < threshold {
-                            writer.serialize(&data_length).await?;
-                            writer.serialize(&VarInt(0i32)).await?;
-
-                            return data.0.write_to(writer).await;
-                        }
-                    }
-
-                    let mut zlib_buf = vec![];
+        let frame = self.frame(compression_threshold)?;
 
-                    data.write_to(&mut zlib_buf).await?;
+        Ok(writer.write_all(&frame).await?)
+    }
 
-                    // length of data length + compressed length of (packet id + data)
-                    let packet_len = data_length.length() + zlib_buf.len();
-                    let packet_length = VarInt::<i32>(packet_len.try_into().unwrap());
+    /// Puts the whole frame, length prefix included, into `buf`.
+    pub fn encode_frame(
+        &self,
+        buf: &mut impl BufMut,
+        compression_threshold: Option<usize>,
+    ) -> Result<()> {
+        let mut body = BytesMut::new();
 
-                    writer.serialize(&packet_length).await?;
-                    writer.serialize(&data_length).await?;
-                    io::copy(&zlib_buf[..], writer).await?;
+        match self {
+            Self::Uncompressed(data) => data.encode(&mut body)?,
+            Self::Compressed(data) => match compression_threshold {
+                Some(threshold) if data.0.length() >= threshold => {
+                    VarInt::<i32>::usize(data.0.length()).encode(&mut body)?;
+                    data.encode(&mut body)?;
+                }
+                _ => {
+                    // a data length of 0 marks the packet as not compressed
+                    VarInt(0i32).encode(&mut body)?;
+                    data.0.encode(&mut body)?;
                 }
-            }
+            },
         }
+
+        VarInt::<i32>::usize(body.len()).encode(buf)?;
+        buf.put_slice(&body);
+        Ok(())
     }
 
-    pub async fn read_from(reader: &mut impl ReadExt, compressing: bool) -> Result<Self> {
+    /// Frames `data` the way a connection that is `compressing` or not expects.
+    pub fn new(data: PacketData, compressing: bool) -> Self {
         if compressing {
-            Self::read_compressed(reader).await
+            Self::Compressed(Zlib(data))
         } else {
-            Self::read_normal(reader).await
+            Self::Uncompressed(data)
         }
     }
 
-    async fn read_normal(reader: &mut impl ReadExt) -> Result<Self> {
-        try {
-            let length = VarInt::<i32>::read_from(reader).await?;
+    /// Encodes `packet` into a whole frame, compressed once `compressing` is set.
+    pub fn encode_packet<T: Encode + Packet>(
+        packet: &T,
+        compressing: bool,
+        compression_threshold: Option<usize>,
+    ) -> Result<Bytes> {
+        Self::new(PacketData::from_packet(packet)?, compressing).frame(compression_threshold)
+    }
 
-            Self::Uncompressed(
-                reader
-                    .deserialize_with_context(&length.0.try_into().unwrap())
-                    .await?,
-            )
+    /// Like [`SerializedPacket::encode_frame`], into a buffer of its own.
+    pub fn frame(&self, compression_threshold: Option<usize>) -> Result<Bytes> {
+        let mut frame = BytesMut::new();
+        self.encode_frame(&mut frame, compression_threshold)?;
+        Ok(frame.freeze())
+    }
+
+    /// Reads one frame into memory and decodes it there.
+    pub async fn read_from(
+        reader: &mut impl ReadExt,
+        compression_threshold: Option<usize>,
+        limits: &FrameLimits,
+    ) -> Result<Self> {
+        let length = VarInt::<i32>::read_from(reader).await?.try_usize()?;
+        if length > limits.max_frame_length {
+            return Err(Error::FrameTooLarge {
+                length,
+                max: limits.max_frame_length,
+            });
         }
+
+        let mut frame = BytesMut::zeroed(length);
+        reader.read_exact(&mut frame).await?;
+
+        Self::decode_frame(&mut frame.freeze(), compression_threshold, limits)
     }
 
-    async fn read_compressed(reader: &mut impl ReadExt) -> Result<Self> {
-        try {
-            let packet_length = VarInt::<i32>::read_from(reader).await?;
-            let VarInt(data_length) = VarInt::<i32>::read_from(reader).await?;
-
-            // very funny
-            if data_length == 0 {
-                Self::Uncompressed(
-                    reader
-                        .deserialize_with_context(&packet_length.0.try_into().unwrap())
-                        .await?,
-                )
-            } else {
-                Self::Compressed(
-                    reader
-                        .deserialize_with_context(&packet_length.0.try_into().unwrap())
-                        .await?,
-                )
-            }
+    /// Decodes a frame whose length prefix has already been taken off.
+    pub fn decode_frame(
+        buf: &mut impl Buf,
+        compression_threshold: Option<usize>,
+        limits: &FrameLimits,
+    ) -> Result<Self> {
+        let Some(threshold) = compression_threshold else {
+            return Ok(Self::Uncompressed(PacketData::decode(buf)?));
+        };
+
+        let data_length = VarInt::<i32>::decode(buf)?.try_usize()?;
+
+        // very funny
+        if data_length == 0 {
+            return Ok(Self::Uncompressed(PacketData::decode(buf)?));
+        }
+
+        if data_length < threshold {
+            return Err(Error::BelowCompressionThreshold {
+                length: data_length,
+                threshold,
+            });
+        }
+        if data_length > limits.max_data_length {
+            return Err(Error::DataTooLarge {
+                length: data_length,
+                max: limits.max_data_length,
+            });
+        }
+
+        // the claim alone mustn't reserve more than the compressed bytes could plausibly
+        // inflate to, what a bomb inflates past that is allocated as it actually arrives
+        let mut data = Vec::with_capacity(data_length.min(buf.remaining() * Self::INFLATION_GUESS));
+        // inflating one byte more than claimed is enough to tell it lied
+        flate2::read::ZlibDecoder::new(buf.reader())
+            .take(data_length as u64 + 1)
+            .read_to_end(&mut data)?;
+
+        if data.len() != data_length {
+            return Err(Error::DataLengthMismatch {
+                expected: data_length,
+                actual: data.len(),
+            });
+        }
+
+        Ok(Self::Compressed(Zlib(PacketData::decode(
+            &mut Bytes::from(data),
+        )?)))
+    }
+}
+
+/// Bounds on received frames, checked before anything is allocated for them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameLimits {
+    /// The longest frame accepted, length prefix excluded.
+    pub max_frame_length: usize,
+    /// The most a compressed packet may inflate to.
+    pub max_data_length: usize,
+    /// How many bytes decoding may allocate for strings and collections, all at once or
+    /// every second over the life of a connection.
+    pub decode_budget: usize,
+}
+
+impl FrameLimits {
+    /// The most a 3 byte VarInt can hold, which is all vanilla reads for a frame length.
+    pub const MAX_FRAME_LENGTH: usize = (1 << 21) - 1;
+    /// Vanilla's limit on the data length of compressed packets.
+    pub const MAX_DATA_LENGTH: usize = 8 * 1024 * 1024;
+    /// Enough for any vanilla packet, a chunk is the largest at a few hundred KiB.
+    pub const DECODE_BUDGET: usize = 16 * 1024 * 1024;
+}
+
+impl Default for FrameLimits {
+    fn default() -> Self {
+        Self {
+            max_frame_length: Self::MAX_FRAME_LENGTH,
+            max_data_length: Self::MAX_DATA_LENGTH,
+            decode_budget: Self::DECODE_BUDGET,
+        }
+    }
+}
+
+/// What a connection may still allocate while decoding its frames.
+///
+/// Every frame spends from it and it refills over time, so neither one frame nor a stream
+/// of them can make us allocate more than [`FrameLimits::decode_budget`] a second.
+#[derive(Clone, Copy, Debug)]
+pub struct DecodeBudget {
+    remaining: usize,
+    capacity: usize,
+    refilled: Instant,
+}
+
+impl DecodeBudget {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            remaining: capacity,
+            capacity,
+            refilled: Instant::now(),
         }
     }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Runs `decode` within what is left of the budget, see [`with_decode_budget`].
+    pub fn spend<R>(&mut self, decode: impl FnOnce() -> R) -> R {
+        self.refill();
+        with_decode_budget(&mut self.remaining, decode)
+    }
+
+    fn refill(&mut self) {
+        let elapsed = self.refilled.elapsed();
+        let refill = (self.capacity as f64 * elapsed.as_secs_f64()) as usize;
+
+        // otherwise frames that come quickly enough would never refill it
+        if refill > 0 {
+            self.remaining = self.remaining.saturating_add(refill).min(self.capacity);
+            self.refilled += elapsed;
+        }
+    }
+}
+
+impl Default for DecodeBudget {
+    fn default() -> Self {
+        Self::new(FrameLimits::DECODE_BUDGET)
+    }
 }
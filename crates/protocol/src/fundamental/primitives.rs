@@ -0,0 +1,521 @@
+use std::{fmt, io::ErrorKind, marker::PhantomData, str::FromStr};
+
+use super::*;
+
+/// A block position, packed into 26 bits of x, 26 bits of z and 12 bits of y.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn pack(&self) -> u64 {
+        ((self.x as u64 & 0x3ff_ffff) << 38)
+            | ((self.z as u64 & 0x3ff_ffff) << 12)
+            | (self.y as u64 & 0xfff)
+    }
+
+    pub fn unpack(packed: u64) -> Self {
+        // shifting the field to the top first sign extends it on the way back down
+        let packed = packed as i64;
+        Self {
+            x: (packed >> 38) as i32,
+            y: (packed << 52 >> 52) as i32,
+            z: (packed << 26 >> 38) as i32,
+        }
+    }
+}
+
+impl AsyncSerialize for Position {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        self.pack().write_to(writer).await
+    }
+}
+
+impl AsyncDeserialize for Position {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        u64::read_from(reader).await.map(Self::unpack)
+    }
+}
+
+impl Encode for Position {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.pack().encode(buf)
+    }
+}
+
+impl Decode for Position {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        u64::decode(buf).map(Self::unpack)
+    }
+}
+
+/// A rotation in steps of 1/256 of a full turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Angle(pub u8);
+
+impl Angle {
+    /// Rounds to the nearest step, wrapping around at a full turn.
+    pub fn from_degrees(degrees: f32) -> Self {
+        Self((degrees / 360.0 * 256.0).round().rem_euclid(256.0) as u8)
+    }
+
+    pub fn degrees(&self) -> f32 {
+        self.0 as f32 * 360.0 / 256.0
+    }
+}
+
+impl AsyncSerialize for Angle {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        self.0.write_to(writer).await
+    }
+}
+
+impl AsyncDeserialize for Angle {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        u8::read_from(reader).await.map(Self)
+    }
+}
+
+impl Encode for Angle {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.0.encode(buf)
+    }
+}
+
+impl Decode for Angle {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        u8::decode(buf).map(Self)
+    }
+}
+
+/// A growable set of bits, written as a VarInt prefixed array of longs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BitSet(pub Vec<u64>);
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.0
+            .get(index / 64)
+            .is_some_and(|long| long & (1 << (index % 64)) != 0)
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        if index / 64 >= self.0.len() {
+            if !value {
+                return;
+            }
+            self.0.resize(index / 64 + 1, 0);
+        }
+
+        let long = &mut self.0[index / 64];
+        if value {
+            *long |= 1 << (index % 64);
+        } else {
+            *long &= !(1 << (index % 64));
+        }
+    }
+}
+
+impl AsyncSerialize for BitSet {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        self.0.write_to(writer).await
+    }
+}
+
+impl AsyncDeserialize for BitSet {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        Vec::read_from(reader).await.map(Self)
+    }
+}
+
+impl Encode for BitSet {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.0.encode(buf)
+    }
+}
+
+impl Decode for BitSet {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Vec::decode(buf).map(Self)
+    }
+}
+
+/// A set of `BYTES * 8` bits, written without a length prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedBitSet<const BYTES: usize>(pub [u8; BYTES]);
+
+impl<const BYTES: usize> Default for FixedBitSet<BYTES> {
+    fn default() -> Self {
+        Self([0; BYTES])
+    }
+}
+
+impl<const BYTES: usize> FixedBitSet<BYTES> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Panics
+    ///
+    /// If `index` is not below `BYTES * 8`.
+    pub fn get(&self, index: usize) -> bool {
+        self.0[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    /// # Panics
+    ///
+    /// If `index` is not below `BYTES * 8`.
+    pub fn set(&mut self, index: usize, value: bool) {
+        if value {
+            self.0[index / 8] |= 1 << (index % 8);
+        } else {
+            self.0[index / 8] &= !(1 << (index % 8));
+        }
+    }
+}
+
+impl<const BYTES: usize> AsyncSerialize for FixedBitSet<BYTES> {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        Ok(writer.write_all(&self.0).await?)
+    }
+}
+
+impl<const BYTES: usize> AsyncDeserialize for FixedBitSet<BYTES> {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        let mut bytes = [0; BYTES];
+        reader.read_exact(&mut bytes).await?;
+        Ok(Self(bytes))
+    }
+}
+
+impl<const BYTES: usize> Encode for FixedBitSet<BYTES> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        buf.put_slice(&self.0);
+        Ok(())
+    }
+}
+
+impl<const BYTES: usize> Decode for FixedBitSet<BYTES> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        let mut bytes = [0; BYTES];
+
+        ensure_remaining(buf, BYTES)?;
+        buf.copy_to_slice(&mut bytes);
+
+        Ok(Self(bytes))
+    }
+}
+
+/// An enum that can be stored in an [`EnumSet`], by the position of its variants.
+pub trait EnumSetType: Copy {
+    /// How many variants there are, at most 64.
+    const VARIANTS: usize;
+
+    fn ordinal(self) -> usize;
+    fn from_ordinal(ordinal: usize) -> Option<Self>;
+}
+
+/// A set of enum variants, written as a fixed bitset of one bit per variant.
+pub struct EnumSet<E> {
+    bits: u64,
+    _enum: PhantomData<E>,
+}
+
+impl<E: EnumSetType> EnumSet<E> {
+    const BYTES: usize = E::VARIANTS.div_ceil(8);
+
+    pub fn new() -> Self {
+        Self {
+            bits: 0,
+            _enum: PhantomData,
+        }
+    }
+
+    pub fn insert(&mut self, value: E) {
+        self.bits |= 1 << value.ordinal();
+    }
+
+    pub fn remove(&mut self, value: E) {
+        self.bits &= !(1 << value.ordinal());
+    }
+
+    pub fn contains(&self, value: E) -> bool {
+        self.bits & (1 << value.ordinal()) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = E> + '_ {
+        (0..E::VARIANTS)
+            .filter(|ordinal| self.bits & (1 << ordinal) != 0)
+            .filter_map(E::from_ordinal)
+    }
+
+    fn bytes(self) -> Vec<u8> {
+        self.bits.to_le_bytes()[..Self::BYTES].to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut bits = [0; 8];
+        bits[..bytes.len()].copy_from_slice(bytes);
+
+        Self {
+            // bits past the last variant mean nothing
+            bits: u64::from_le_bytes(bits)
+                & u64::MAX.checked_shr(64 - E::VARIANTS as u32).unwrap_or(0),
+            _enum: PhantomData,
+        }
+    }
+}
+
+// written out since deriving them would require `E` to implement them too
+impl<E> Clone for EnumSet<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for EnumSet<E> {}
+
+impl<E> PartialEq for EnumSet<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<E> Eq for EnumSet<E> {}
+
+impl<E: EnumSetType> Default for EnumSet<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: EnumSetType + fmt::Debug> fmt::Debug for EnumSet<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<E: EnumSetType> FromIterator<E> for EnumSet<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+impl<E: EnumSetType> AsyncSerialize for EnumSet<E> {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        Ok(writer.write_all(&self.bytes()).await?)
+    }
+}
+
+impl<E: EnumSetType> AsyncDeserialize for EnumSet<E> {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        let mut bytes = vec![0; Self::BYTES];
+        reader.read_exact(&mut bytes).await?;
+        Ok(Self::from_bytes(&bytes))
+    }
+}
+
+impl<E: EnumSetType> Encode for EnumSet<E> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        buf.put_slice(&self.bytes());
+        Ok(())
+    }
+}
+
+impl<E: EnumSetType> Decode for EnumSet<E> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        ensure_remaining(buf, Self::BYTES)?;
+        Ok(Self::from_bytes(&buf.copy_to_bytes(Self::BYTES)))
+    }
+}
+
+/// A namespaced location such as `minecraft:stone`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Identifier {
+    namespace: String,
+    path: String,
+}
+
+impl Identifier {
+    /// The namespace assumed when none is given.
+    pub const DEFAULT_NAMESPACE: &'static str = "minecraft";
+
+    pub fn new(namespace: impl Into<String>, path: impl Into<String>) -> Result<Self> {
+        let (namespace, path) = (namespace.into(), path.into());
+
+        let valid = |string: &str, extra: &[u8]| {
+            string.bytes().all(|byte| {
+                matches!(byte, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.')
+                    || extra.contains(&byte)
+            })
+        };
+        if namespace.is_empty() || !valid(&namespace, b"") || !valid(&path, b"/") {
+            return Err(Error::InvalidIdentifier(format!("{namespace}:{path}")));
+        }
+
+        Ok(Self { namespace, path })
+    }
+
+    /// An identifier in the `minecraft` namespace.
+    pub fn minecraft(path: impl Into<String>) -> Result<Self> {
+        Self::new(Self::DEFAULT_NAMESPACE, path)
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+impl FromStr for Identifier {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self> {
+        // like vanilla, an empty namespace is the default one too
+        match string.split_once(':') {
+            Some((namespace, path)) if !namespace.is_empty() => Self::new(namespace, path),
+            Some((_, path)) => Self::minecraft(path),
+            None => Self::minecraft(string),
+        }
+    }
+}
+
+impl serde::Serialize for Identifier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Identifier {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl AsyncSerialize for Identifier {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        self.to_string().write_to(writer).await
+    }
+}
+
+impl AsyncDeserialize for Identifier {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        String::read_from(reader).await?.parse()
+    }
+}
+
+impl Encode for Identifier {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.to_string().encode(buf)
+    }
+}
+
+impl Decode for Identifier {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        String::decode(buf)?.parse()
+    }
+}
+
+/// Bytes with a VarInt length prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ByteArray(pub Bytes);
+
+impl AsyncSerialize for ByteArray {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        VarInt::<i32>::usize(self.0.len()).write_to(writer).await?;
+        write_rest(writer, &self.0).await
+    }
+}
+
+impl AsyncDeserialize for ByteArray {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        let length = VarInt::<i32>::read_from(reader).await?.try_usize()?;
+
+        // don't allocate whatever the length claims before the bytes actually arrive
+        let mut bytes = vec![];
+        reader.take(length as u64).read_to_end(&mut bytes).await?;
+        if bytes.len() < length {
+            return Err(Error::Io(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "Unexpected end of file",
+            )));
+        }
+
+        Ok(Self(bytes.into()))
+    }
+}
+
+impl Encode for ByteArray {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        VarInt::<i32>::usize(self.0.len()).encode(buf)?;
+        encode_rest(buf, &self.0)
+    }
+}
+
+impl Decode for ByteArray {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        let length = VarInt::<i32>::decode(buf)?.try_usize()?;
+        ensure_remaining(buf, length)?;
+        spend_decode_budget(length)?;
+
+        Ok(Self(buf.copy_to_bytes(length)))
+    }
+}
+
+/// Everything up to the end of the packet, so it must be the last field.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct RemainingBytes(pub Bytes);
+
+impl AsyncSerialize for RemainingBytes {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        write_rest(writer, &self.0).await
+    }
+}
+
+impl AsyncDeserialize for RemainingBytes {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        read_rest(reader).await.map(Self)
+    }
+}
+
+impl Encode for RemainingBytes {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        encode_rest(buf, &self.0)
+    }
+}
+
+impl Decode for RemainingBytes {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        decode_rest(buf).map(Self)
+    }
+}
@@ -1,16 +1,12 @@
 use super::*;
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct VarInt<T: VarNumber = i32>(pub T);
 
 impl<T: VarNumber + PartialEq<T>> PartialEq<T> for VarInt<T> {
     fn eq(&self, other: &T) -> bool {
         self.0.eq(other)
     }
-
-    fn ne(&self, other: &T) -> bool {
-        self.0.ne(other)
-    }
 }
 
 impl<T: VarNumber> VarInt<T> {
@@ -33,6 +29,13 @@ impl<T: VarNumber> VarInt<T> {
     }
 }
 
+impl VarInt<i32> {
+    /// For lengths read off the wire, which may be negative.
+    pub fn try_usize(&self) -> Result<usize> {
+        self.0.try_into().map_err(|_| Error::NegativeLength(self.0))
+    }
+}
+
 impl<T: VarNumber> AsyncSerialize for VarInt<T> {
     async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
         self.0.write_var(writer).await
@@ -45,79 +48,111 @@ impl<T: VarNumber> AsyncDeserialize for VarInt<T> {
     }
 }
 
+impl<T: VarNumber> Encode for VarInt<T> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.0.encode_var(buf);
+        Ok(())
+    }
+}
+
+impl<T: VarNumber> Decode for VarInt<T> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        T::decode_var(buf).map(Self)
+    }
+}
+
 // a trait to abstract away how the numbers are written to the streams
+//
+// every number is written as unsigned LEB128 over its two's complement bits, 7 bits per
+// byte starting from the lowest, so negative numbers always take the maximum length
 #[doc(hidden)]
 pub trait VarNumber: Sized + Copy {
-    async fn read_var(reader: &mut impl ReadExt) -> Result<Self>;
-    async fn write_var(self, writer: &mut impl WriteExt) -> Result<()>;
-    fn length(&self) -> usize;
-}
-
-macro_rules! impl_varnum_signed {
-    ($($ty:ty),*) => {
-        $(impl VarNumber for $ty {
-            async fn read_var(reader: &mut impl ReadExt) -> Result<Self> {
-                let mut result = 0;
-                let mut shift = 0;
-                let mut byte: u8;
-
-                loop {
-                    byte = reader.byte().await?;
-                    result |= Self::from(byte & 0x7f) << shift;
-                    shift += 7;
-
-                    if (byte & 0x80) == 0 {
-                        break;
-                    }
-                }
-
-                if (shift < Self::BITS) && ((byte & 0x40) != 0) {
-                    result |= !0 << shift;
-                }
-
-                Ok(result)
+    /// The most bytes an encoding may take, longer ones are rejected.
+    const MAX_LENGTH: usize;
+
+    /// The number's bits, zero extended.
+    fn to_bits(self) -> u64;
+    /// Truncates `bits` to the number's width, like vanilla does with the spare bits of the
+    /// last byte.
+    fn from_bits(bits: u64) -> Self;
+
+    async fn read_var(reader: &mut impl ReadExt) -> Result<Self> {
+        let mut bits = 0;
+
+        for index in 0..Self::MAX_LENGTH {
+            let byte = reader.byte().await?;
+            bits |= u64::from(byte & 0x7f)
+                .checked_shl(7 * index as u32)
+                .unwrap_or(0);
+
+            if byte & 0x80 == 0 {
+                return Ok(Self::from_bits(bits));
             }
+        }
 
-            async fn write_var(mut self, writer: &mut impl WriteExt) -> Result<()> {
-                loop {
-                    let byte = (self as u8) & 0x7f;
-                    self >>= 7;
-                    let more = !(((self == 0) && ((byte & 0x40) == 0))
-                        || ((self == -1) && ((byte & 0x40) != 0)));
+        Err(Error::VarIntTooLong(Self::MAX_LENGTH))
+    }
+
+    async fn write_var(self, writer: &mut impl WriteExt) -> Result<()> {
+        let mut bytes = [0; 10];
+        let mut buf = &mut bytes[..];
+        self.encode_var(&mut buf);
+        let length = 10 - buf.len();
+
+        Ok(writer.write_all(&bytes[..length]).await?)
+    }
 
-                    writer
-                        .byte(byte | more.then_some(0x80).unwrap_or_default())
-                        .await?;
+    fn decode_var(buf: &mut impl Buf) -> Result<Self> {
+        let mut bits = 0;
 
-                    if !more {
-                        break;
-                    }
-                }
+        for index in 0..Self::MAX_LENGTH {
+            let byte = u8::decode(buf)?;
+            bits |= u64::from(byte & 0x7f)
+                .checked_shl(7 * index as u32)
+                .unwrap_or(0);
 
-                Ok(())
+            if byte & 0x80 == 0 {
+                return Ok(Self::from_bits(bits));
             }
+        }
 
-            fn length(&self) -> usize {
-                let mut value = *self;
-                let mut len = 0;
+        Err(Error::VarIntTooLong(Self::MAX_LENGTH))
+    }
 
-                loop {
-                    let byte = (value as u8) & 0x7f;
-                    value >>= 7;
-                    let more = !(((value == 0) && ((byte & 0x40) == 0))
-                        || ((value == -1) && ((byte & 0x40) != 0)));
+    fn encode_var(self, buf: &mut impl BufMut) {
+        let mut bits = self.to_bits();
 
-                    len += 1;
+        while bits >= 0x80 {
+            buf.put_u8(bits as u8 | 0x80);
+            bits >>= 7;
+        }
+        buf.put_u8(bits as u8);
+    }
 
-                    if !more {
-                        break;
-                    }
-                }
+    fn length(&self) -> usize {
+        let used = u64::BITS - self.to_bits().leading_zeros();
+        (used as usize).div_ceil(7).max(1)
+    }
+}
+
+macro_rules! impl_varnum {
+    ($($ty:ty => $unsigned:ty),*) => {
+        $(impl VarNumber for $ty {
+            const MAX_LENGTH: usize = (<$ty>::BITS as usize).div_ceil(7);
 
-                len
+            fn to_bits(self) -> u64 {
+                self as $unsigned as u64
+            }
+
+            fn from_bits(bits: u64) -> Self {
+                bits as $unsigned as $ty
             }
         })*
     };
 }
 
-impl_varnum_signed!(i32, i64);
+// the unsigned ones read the same bytes, for fields that are never negative
+impl_varnum!(i32 => u32, i64 => u64, u32 => u32, u64 => u64);
+
+/// A VarInt of 64 bits, up to 10 bytes long.
+pub type VarLong = VarInt<i64>;
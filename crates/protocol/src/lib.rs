@@ -1,21 +1,33 @@
 #![feature(try_blocks)]
 #![allow(async_fn_in_trait)]
 
+// lets the derives refer to `::arte_protocol` from inside this crate too
+extern crate self as arte_protocol;
+
 use bytes::*;
 
 use async_std::{
-    io::{BufReader, Read, ReadExt as AsyncStdReadExt, Write},
+    io::{BufReader, Read, ReadExt as AsyncStdReadExt, Write, WriteExt as AsyncStdWriteExt},
     net::TcpStream,
 };
 
 pub const PROTOCOL_VERSION: VarInt = VarInt(763);
+pub const VERSION_NAME: &str = "1.20.1";
 
 pub mod fundamental;
 use fundamental::*;
 
 pub mod model;
+pub mod nbt;
+mod network;
 pub mod phases;
+pub mod registry;
 
+pub use arte_macros::{AsyncDeserialize, AsyncSerialize, Decode, Encode, Packet};
+pub use network::*;
+// the derives name `Buf` and `BufMut` through this
+#[doc(hidden)]
+pub use bytes;
 pub use uuid::{uuid as comptime_uuid, Uuid};
 
 #[derive(Debug, thiserror::Error)]
@@ -40,11 +52,60 @@ pub enum Error {
         // for Debug output
         got: String,
     },
+    #[error("collection of {length} items is larger than the maximum of {max}")]
+    CollectionTooLarge { length: usize, max: usize },
+    #[error("decoding needs {needed} more bytes, but only {remaining} are left in the budget")]
+    DecodeBudgetExceeded { needed: usize, remaining: usize },
+    #[error("invalid identifier {_0:?}")]
+    InvalidIdentifier(String),
+    #[error("VarInt is longer than {_0} bytes")]
+    VarIntTooLong(usize),
+    #[error("negative length {_0}")]
+    NegativeLength(i32),
+    #[error("frame of {length} bytes is larger than the maximum of {max}")]
+    FrameTooLarge { length: usize, max: usize },
+    #[error("packet inflates to {length} bytes, more than the maximum of {max}")]
+    DataTooLarge { length: usize, max: usize },
+    #[error(
+        "compressed packet of {length} bytes is below the compression threshold of {threshold}"
+    )]
+    BelowCompressionThreshold { length: usize, threshold: usize },
+    #[error("packet claims to inflate to {expected} bytes, but inflates to {actual}")]
+    DataLengthMismatch { expected: usize, actual: usize },
+    #[error("the queue of {_0} packets waiting to be sent is full")]
+    SendQueueFull(usize),
+    #[error("the connection is closed")]
+    ConnectionClosed,
+    #[error("unknown packet id {id:?} in state {state:?}")]
+    UnknownPacket { id: VarInt, state: State },
     #[error("invalid protocol version, expected {PROTOCOL_VERSION:?}, got {_0:?}")]
     InvalidProtocolVersion(VarInt),
 
     #[error(transparent)]
     Json(#[from] serde_json::Error),
+    #[error("invalid NBT: {_0}")]
+    Nbt(String),
+    #[error("NBT nests deeper than {_0} levels")]
+    NbtTooDeep(usize),
+    #[error("NBT is larger than {_0} bytes")]
+    NbtTooLarge(usize),
+
+    #[error(transparent)]
+    Rsa(#[from] rsa::Error),
+    #[error("the verify token sent back by the client does not match")]
+    InvalidVerifyToken,
+    #[error("shared secret must be 16 bytes long, got {_0}")]
+    InvalidSharedSecret(usize),
+    #[error("authentication failed: {_0}")]
+    Authentication(String),
+    #[error("player info forwarding failed: {_0}")]
+    Forwarding(String),
+    #[error("got a login plugin response with unknown message id {_0:?}")]
+    UnexpectedPluginResponse(VarInt),
+    #[error("the client didn't answer a keep alive in time")]
+    KeepAliveTimeout,
+    #[error("got a keep alive with unexpected id {_0}")]
+    UnexpectedKeepAlive(i64),
 }
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -63,6 +124,23 @@ pub trait AsyncSerialize {
     async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()>;
 }
 
+/// Like [`AsyncDeserialize`], but decodes from a buffer already in memory.
+pub trait Decode: Sized {
+    fn decode(buf: &mut impl Buf) -> Result<Self>;
+}
+
+/// Like [`AsyncDeserializeContexful`], but decodes from a buffer already in memory.
+pub trait DecodeContextful: Sized {
+    type Context;
+
+    fn decode_with_context(buf: &mut impl Buf, context: &Self::Context) -> Result<Self>;
+}
+
+/// Like [`AsyncSerialize`], but encodes into a buffer in memory.
+pub trait Encode {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()>;
+}
+
 pub trait Packet {
     const ID: VarInt<i32>;
     const STATE: State;
@@ -86,10 +164,13 @@ pub trait PacketHandle<Context>: Packet {
 }
 
 pub struct PlayerNetwork {
-    pub tcp: TcpStream,
+    pub tcp: CipherStream<TcpStream>,
     pub compressing: bool,
     pub state: State,
     pub compression_threshold: Option<usize>,
+    pub frame_limits: FrameLimits,
+    /// Spent by every packet received, see [`FrameLimits::decode_budget`].
+    pub decode_budget: DecodeBudget,
 }
 
 pub struct PacketContext {
@@ -106,23 +187,31 @@ pub enum State {
 }
 
 impl PlayerNetwork {
-    pub async fn recv_packet<T: AsyncDeserializeContexful<Context = PacketContext>>(
-        &mut self,
-    ) -> Result<T> {
-        SerializedPacket::read_packet(&mut self.tcp, self.compressing, self.state).await
+    pub async fn recv_packet<T: DecodeContextful<Context = PacketContext>>(&mut self) -> Result<T> {
+        let threshold = self.receive_threshold();
+        SerializedPacket::read_packet(
+            &mut self.tcp,
+            threshold,
+            &self.frame_limits,
+            &mut self.decode_budget,
+            self.state,
+        )
+        .await
     }
 
-    pub async fn send_packet<T: AsyncSerialize + Packet>(&mut self, packet: T) -> Result<()> {
-        let mut data = vec![];
+    /// The threshold received frames are checked against, `Some` once compression is enabled.
+    pub fn receive_threshold(&self) -> Option<usize> {
+        // a peer that didn't say what threshold it uses could be using any
+        self.compressing
+            .then(|| self.compression_threshold.unwrap_or(0))
+    }
 
-        packet.write_to(&mut data).await?;
+    pub async fn send_packet<T: Encode + Packet>(&mut self, packet: T) -> Result<()> {
+        let frame =
+            SerializedPacket::encode_packet(&packet, self.compressing, self.compression_threshold)?;
 
-        SerializedPacket::Uncompressed(PacketData {
-            packet_id: T::ID,
-            data: data.into(),
-        })
-        .write_to(&mut self.tcp, self.compression_threshold)
-        .await
+        self.tcp.write_all(&frame).await?;
+        Ok(self.tcp.flush().await?)
     }
 }
 
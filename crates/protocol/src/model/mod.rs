@@ -1,3 +1,8 @@
+use super::*;
+
+mod text;
+pub use text::*;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum Gamemode {
@@ -6,3 +11,252 @@ pub enum Gamemode {
     Adventure = 2,
     Spectator = 3,
 }
+
+impl Gamemode {
+    fn from_id(id: u8) -> Result<Self> {
+        Ok(read_enum! { [id]
+            0 => Self::Survival,
+            1 => Self::Creative,
+            2 => Self::Adventure,
+            3 => Self::Spectator,
+        })
+    }
+}
+
+impl AsyncSerialize for Gamemode {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        writer.serialize(&(*self as u8)).await
+    }
+}
+
+impl AsyncDeserialize for Gamemode {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        Self::from_id(reader.deserialize().await?)
+    }
+}
+
+impl Encode for Gamemode {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        (*self as u8).encode(buf)
+    }
+}
+
+impl Decode for Gamemode {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Self::from_id(u8::decode(buf)?)
+    }
+}
+
+/// A gamemode that may be missing, written as `-1` then.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OptionalGamemode(pub Option<Gamemode>);
+
+impl OptionalGamemode {
+    fn id(self) -> i8 {
+        self.0.map_or(-1, |gamemode| gamemode as i8)
+    }
+
+    fn from_id(id: i8) -> Result<Self> {
+        match id {
+            -1 => Ok(Self(None)),
+            id => Gamemode::from_id(id as u8).map(Some).map(Self),
+        }
+    }
+}
+
+impl AsyncSerialize for OptionalGamemode {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        writer.serialize(&self.id()).await
+    }
+}
+
+impl AsyncDeserialize for OptionalGamemode {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        Self::from_id(reader.deserialize().await?)
+    }
+}
+
+impl Encode for OptionalGamemode {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.id().encode(buf)
+    }
+}
+
+impl Decode for OptionalGamemode {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Self::from_id(i8::decode(buf)?)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Difficulty {
+    Peaceful = 0,
+    Easy = 1,
+    #[default]
+    Normal = 2,
+    Hard = 3,
+}
+
+impl Difficulty {
+    fn from_id(id: u8) -> Result<Self> {
+        Ok(read_enum! { [id]
+            0 => Self::Peaceful,
+            1 => Self::Easy,
+            2 => Self::Normal,
+            3 => Self::Hard,
+        })
+    }
+}
+
+impl AsyncSerialize for Difficulty {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        writer.serialize(&(*self as u8)).await
+    }
+}
+
+impl AsyncDeserialize for Difficulty {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        Self::from_id(reader.deserialize().await?)
+    }
+}
+
+impl Encode for Difficulty {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        (*self as u8).encode(buf)
+    }
+}
+
+impl Decode for Difficulty {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Self::from_id(u8::decode(buf)?)
+    }
+}
+
+/// What a player is allowed to do, as sent in Player Abilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ability {
+    Invulnerable,
+    Flying,
+    AllowFlying,
+    /// Breaking blocks instantly, as in creative mode.
+    InstantBreak,
+}
+
+impl EnumSetType for Ability {
+    const VARIANTS: usize = 4;
+
+    fn ordinal(self) -> usize {
+        self as usize
+    }
+
+    fn from_ordinal(ordinal: usize) -> Option<Self> {
+        [
+            Self::Invulnerable,
+            Self::Flying,
+            Self::AllowFlying,
+            Self::InstantBreak,
+        ]
+        .get(ordinal)
+        .copied()
+    }
+}
+
+/// Which parts of a teleport are relative to where the player already is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeMovement {
+    X,
+    Y,
+    Z,
+    Yaw,
+    Pitch,
+}
+
+impl EnumSetType for RelativeMovement {
+    const VARIANTS: usize = 5;
+
+    fn ordinal(self) -> usize {
+        self as usize
+    }
+
+    fn from_ordinal(ordinal: usize) -> Option<Self> {
+        [Self::X, Self::Y, Self::Z, Self::Yaw, Self::Pitch]
+            .get(ordinal)
+            .copied()
+    }
+}
+
+/// Which chat messages the client wants to see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatMode {
+    Enabled = 0,
+    CommandsOnly = 1,
+    Hidden = 2,
+}
+
+impl AsyncSerialize for ChatMode {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        writer.serialize(&VarInt(*self as i32)).await
+    }
+}
+
+impl AsyncDeserialize for ChatMode {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        Ok(read_enum! { [reader.deserialize::<VarInt>().await?.0]
+            0 => Self::Enabled,
+            1 => Self::CommandsOnly,
+            2 => Self::Hidden,
+        })
+    }
+}
+
+impl Encode for ChatMode {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        VarInt(*self as i32).encode(buf)
+    }
+}
+
+impl Decode for ChatMode {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Ok(read_enum! { [VarInt::<i32>::decode(buf)?.0]
+            0 => Self::Enabled,
+            1 => Self::CommandsOnly,
+            2 => Self::Hidden,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MainHand {
+    Left = 0,
+    Right = 1,
+}
+
+impl AsyncSerialize for MainHand {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        writer.serialize(&VarInt(*self as i32)).await
+    }
+}
+
+impl AsyncDeserialize for MainHand {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        Ok(read_enum! { [reader.deserialize::<VarInt>().await?.0]
+            0 => Self::Left,
+            1 => Self::Right,
+        })
+    }
+}
+
+impl Encode for MainHand {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        VarInt(*self as i32).encode(buf)
+    }
+}
+
+impl Decode for MainHand {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        Ok(read_enum! { [VarInt::<i32>::decode(buf)?.0]
+            0 => Self::Left,
+            1 => Self::Right,
+        })
+    }
+}
@@ -0,0 +1,567 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::Uuid;
+
+/// Minecraft's JSON text component, used for chat, disconnect reasons, MOTDs and titles.
+///
+/// Children in `extra` inherit the style of their parent unless they override it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextComponent {
+    #[serde(flatten)]
+    pub content: TextContent,
+    #[serde(flatten)]
+    pub style: Style,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<TextComponent>,
+}
+
+/// What a component displays, before any of its children.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TextContent {
+    Text {
+        text: String,
+    },
+    Translation {
+        translate: String,
+        /// Substituted for the `%s` placeholders of the translation.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        with: Vec<TextComponent>,
+    },
+    Score {
+        score: Score,
+    },
+    Selector {
+        selector: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        separator: Option<Box<TextComponent>>,
+    },
+    /// Displays the key bound to e.g. `key.inventory`.
+    Keybind {
+        keybind: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    /// A player name or a selector.
+    pub name: String,
+    pub objective: String,
+    /// Shown instead of looking up the score.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// Formatting of a component, `None` inherits from the parent.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Style {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underlined: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obfuscated: Option<bool>,
+    /// A resource location such as `minecraft:uniform`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    /// Inserted into the chat box when the component is shift-clicked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insertion: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub click_event: Option<ClickEvent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hover_event: Option<HoverEvent>,
+}
+
+impl Style {
+    /// Fills everything this style leaves unset from `parent`.
+    pub fn inherit(&self, parent: &Style) -> Style {
+        Style {
+            color: self.color.or(parent.color),
+            bold: self.bold.or(parent.bold),
+            italic: self.italic.or(parent.italic),
+            underlined: self.underlined.or(parent.underlined),
+            strikethrough: self.strikethrough.or(parent.strikethrough),
+            obfuscated: self.obfuscated.or(parent.obfuscated),
+            font: self.font.clone().or_else(|| parent.font.clone()),
+            insertion: self.insertion.clone().or_else(|| parent.insertion.clone()),
+            click_event: self
+                .click_event
+                .clone()
+                .or_else(|| parent.click_event.clone()),
+            hover_event: self
+                .hover_event
+                .clone()
+                .or_else(|| parent.hover_event.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "value", rename_all = "snake_case")]
+pub enum ClickEvent {
+    OpenUrl(String),
+    RunCommand(String),
+    SuggestCommand(String),
+    /// The page number of a book, as a string.
+    ChangePage(String),
+    CopyToClipboard(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "contents", rename_all = "snake_case")]
+pub enum HoverEvent {
+    ShowText(Box<TextComponent>),
+    ShowItem {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        count: Option<i32>,
+        /// The item's NBT as SNBT.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
+    },
+    ShowEntity {
+        #[serde(rename = "type")]
+        kind: String,
+        id: Uuid,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<Box<TextComponent>>,
+    },
+}
+
+/// One of the 16 named chat colors, or any other written as `#rrggbb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Color {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// The named colors with their names and legacy `§` codes.
+    pub const NAMED: [(Color, &'static str, char); 16] = [
+        (Color::Black, "black", '0'),
+        (Color::DarkBlue, "dark_blue", '1'),
+        (Color::DarkGreen, "dark_green", '2'),
+        (Color::DarkAqua, "dark_aqua", '3'),
+        (Color::DarkRed, "dark_red", '4'),
+        (Color::DarkPurple, "dark_purple", '5'),
+        (Color::Gold, "gold", '6'),
+        (Color::Gray, "gray", '7'),
+        (Color::DarkGray, "dark_gray", '8'),
+        (Color::Blue, "blue", '9'),
+        (Color::Green, "green", 'a'),
+        (Color::Aqua, "aqua", 'b'),
+        (Color::Red, "red", 'c'),
+        (Color::LightPurple, "light_purple", 'd'),
+        (Color::Yellow, "yellow", 'e'),
+        (Color::White, "white", 'f'),
+    ];
+
+    pub fn from_legacy_code(code: char) -> Option<Self> {
+        let code = code.to_ascii_lowercase();
+        Self::NAMED
+            .iter()
+            .find(|(_, _, named)| *named == code)
+            .map(|(color, _, _)| *color)
+    }
+
+    /// `None` for [`Color::Rgb`].
+    pub fn legacy_code(self) -> Option<char> {
+        Self::NAMED
+            .iter()
+            .find(|(color, _, _)| *color == self)
+            .map(|(_, _, code)| *code)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rgb(r, g, b) => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+            named => {
+                let (_, name, _) = Self::NAMED
+                    .iter()
+                    .find(|(color, _, _)| color == named)
+                    .expect("every other color is named");
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix('#') {
+            let rgb = (hex.len() == 6)
+                .then(|| u32::from_str_radix(hex, 16).ok())
+                .flatten()
+                .ok_or_else(|| format!("invalid hex color {s:?}"))?;
+
+            let [_, r, g, b] = rgb.to_be_bytes();
+            return Ok(Self::Rgb(r, g, b));
+        }
+
+        Self::NAMED
+            .iter()
+            .find(|(_, name, _)| *name == s)
+            .map(|(color, _, _)| *color)
+            .ok_or_else(|| format!("unknown color {s:?}"))
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Color> for String {
+    fn from(value: Color) -> Self {
+        value.to_string()
+    }
+}
+
+impl TextComponent {
+    pub fn new(content: TextContent) -> Self {
+        Self {
+            content,
+            style: Style::default(),
+            extra: vec![],
+        }
+    }
+
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new(TextContent::Text { text: text.into() })
+    }
+
+    pub fn translate(key: impl Into<String>) -> Self {
+        Self::translate_with(key, Vec::<TextComponent>::new())
+    }
+
+    pub fn translate_with(
+        key: impl Into<String>,
+        with: impl IntoIterator<Item = impl Into<TextComponent>>,
+    ) -> Self {
+        Self::new(TextContent::Translation {
+            translate: key.into(),
+            with: with.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    pub fn score(name: impl Into<String>, objective: impl Into<String>) -> Self {
+        Self::new(TextContent::Score {
+            score: Score {
+                name: name.into(),
+                objective: objective.into(),
+                value: None,
+            },
+        })
+    }
+
+    pub fn selector(selector: impl Into<String>) -> Self {
+        Self::new(TextContent::Selector {
+            selector: selector.into(),
+            separator: None,
+        })
+    }
+
+    pub fn keybind(keybind: impl Into<String>) -> Self {
+        Self::new(TextContent::Keybind {
+            keybind: keybind.into(),
+        })
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.style.bold = Some(bold);
+        self
+    }
+
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.style.italic = Some(italic);
+        self
+    }
+
+    pub fn underlined(mut self, underlined: bool) -> Self {
+        self.style.underlined = Some(underlined);
+        self
+    }
+
+    pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.style.strikethrough = Some(strikethrough);
+        self
+    }
+
+    pub fn obfuscated(mut self, obfuscated: bool) -> Self {
+        self.style.obfuscated = Some(obfuscated);
+        self
+    }
+
+    pub fn font(mut self, font: impl Into<String>) -> Self {
+        self.style.font = Some(font.into());
+        self
+    }
+
+    pub fn insertion(mut self, insertion: impl Into<String>) -> Self {
+        self.style.insertion = Some(insertion.into());
+        self
+    }
+
+    pub fn click(mut self, event: ClickEvent) -> Self {
+        self.style.click_event = Some(event);
+        self
+    }
+
+    pub fn hover(mut self, event: HoverEvent) -> Self {
+        self.style.hover_event = Some(event);
+        self
+    }
+
+    /// Adds a child to `extra`.
+    pub fn append(mut self, child: impl Into<TextComponent>) -> Self {
+        self.extra.push(child.into());
+        self
+    }
+
+    /// Converts text with legacy `§` formatting codes, including BungeeCord's `§x§r§r§g§g§b§b`
+    /// hex colors. Unknown codes are dropped, like the client does.
+    pub fn from_legacy(text: &str) -> Self {
+        let mut parts = vec![];
+        let mut style = Style::default();
+        let mut current = String::new();
+        let mut chars = text.chars();
+
+        while let Some(char) = chars.next() {
+            if char != '§' {
+                current.push(char);
+                continue;
+            }
+            let Some(code) = chars.next() else {
+                break;
+            };
+
+            if !current.is_empty() {
+                parts.push(Self::text(std::mem::take(&mut current)).with_style(style.clone()));
+            }
+
+            match code.to_ascii_lowercase() {
+                'k' => style.obfuscated = Some(true),
+                'l' => style.bold = Some(true),
+                'm' => style.strikethrough = Some(true),
+                'n' => style.underlined = Some(true),
+                'o' => style.italic = Some(true),
+                'r' => style = Style::default(),
+                'x' => {
+                    let hex: Option<String> = (0..6)
+                        .map(|_| match (chars.next(), chars.next()) {
+                            (Some('§'), Some(digit)) if digit.is_ascii_hexdigit() => Some(digit),
+                            _ => None,
+                        })
+                        .collect();
+
+                    if let Some(color) = hex.and_then(|hex| format!("#{hex}").parse().ok()) {
+                        style = Style {
+                            color: Some(color),
+                            ..Style::default()
+                        };
+                    }
+                }
+                // colors also reset the formatting
+                code => {
+                    if let Some(color) = Color::from_legacy_code(code) {
+                        style = Style {
+                            color: Some(color),
+                            ..Style::default()
+                        };
+                    }
+                }
+            }
+        }
+
+        if !current.is_empty() || parts.is_empty() {
+            parts.push(Self::text(current).with_style(style));
+        }
+
+        if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Self {
+                extra: parts,
+                ..Self::text("")
+            }
+        }
+    }
+
+    /// Flattens the component into text with legacy `§` formatting codes.
+    ///
+    /// Only colors and formatting survive. Translations, keybinds and selectors show their
+    /// key since they are resolved by the client.
+    pub fn to_legacy(&self) -> String {
+        let mut legacy = String::new();
+        self.write_legacy(&Style::default(), &mut Style::default(), &mut legacy);
+        legacy
+    }
+
+    fn write_legacy(&self, parent: &Style, written: &mut Style, legacy: &mut String) {
+        let style = self.style.inherit(parent);
+
+        let text = match &self.content {
+            TextContent::Text { text } => text.as_str(),
+            TextContent::Translation { translate, .. } => translate,
+            TextContent::Score { score } => score.value.as_deref().unwrap_or_default(),
+            TextContent::Selector { selector, .. } => selector,
+            TextContent::Keybind { keybind } => keybind,
+        };
+
+        if !text.is_empty() {
+            let formatting = Style {
+                color: style.color,
+                bold: style.bold.filter(|set| *set),
+                italic: style.italic.filter(|set| *set),
+                underlined: style.underlined.filter(|set| *set),
+                strikethrough: style.strikethrough.filter(|set| *set),
+                obfuscated: style.obfuscated.filter(|set| *set),
+                ..Style::default()
+            };
+
+            if formatting != *written {
+                // setting a color resets the formatting, so either way start from scratch
+                match formatting.color {
+                    Some(Color::Rgb(r, g, b)) => {
+                        legacy.push_str("§x");
+                        for digit in format!("{r:02x}{g:02x}{b:02x}").chars() {
+                            legacy.push('§');
+                            legacy.push(digit);
+                        }
+                    }
+                    Some(color) => {
+                        legacy.push('§');
+                        legacy.extend(color.legacy_code());
+                    }
+                    None => legacy.push_str("§r"),
+                }
+
+                for (set, code) in [
+                    (formatting.obfuscated, 'k'),
+                    (formatting.bold, 'l'),
+                    (formatting.strikethrough, 'm'),
+                    (formatting.underlined, 'n'),
+                    (formatting.italic, 'o'),
+                ] {
+                    if set.is_some() {
+                        legacy.push('§');
+                        legacy.push(code);
+                    }
+                }
+
+                *written = formatting;
+            }
+
+            legacy.push_str(text);
+        }
+
+        for child in &self.extra {
+            child.write_legacy(&style, written, legacy);
+        }
+    }
+
+    fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl Default for TextComponent {
+    fn default() -> Self {
+        Self::text("")
+    }
+}
+
+impl From<&str> for TextComponent {
+    fn from(value: &str) -> Self {
+        Self::text(value)
+    }
+}
+
+impl From<String> for TextComponent {
+    fn from(value: String) -> Self {
+        Self::text(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for TextComponent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // a plain string is a text component, and an array is its first element with the
+        // rest appended to it
+        // only lives until it's converted below
+        #[allow(clippy::large_enum_variant)]
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Text(String),
+            Array(Vec<TextComponent>),
+            Object {
+                #[serde(flatten)]
+                content: TextContent,
+                #[serde(flatten)]
+                style: Style,
+                #[serde(default)]
+                extra: Vec<TextComponent>,
+            },
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Text(text) => Ok(Self::text(text)),
+            Repr::Array(components) => {
+                let mut components = components.into_iter();
+                let mut first = components.next().ok_or_else(|| {
+                    serde::de::Error::invalid_length(0, &"at least one component")
+                })?;
+                first.extra.extend(components);
+                Ok(first)
+            }
+            Repr::Object {
+                content,
+                style,
+                extra,
+            } => Ok(Self {
+                content,
+                style,
+                extra,
+            }),
+        }
+    }
+}
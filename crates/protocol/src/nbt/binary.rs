@@ -0,0 +1,475 @@
+use std::{borrow::Cow, io::ErrorKind};
+
+use super::*;
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::Nbt(message.into())
+}
+
+/// Encodes `string` as Java's modified UTF-8, which writes NUL as two bytes and characters
+/// outside the BMP as a surrogate pair of three bytes each.
+pub fn encode_mutf8(string: &str) -> Cow<'_, [u8]> {
+    if !string.bytes().any(|byte| byte == 0 || byte >= 0xf0) {
+        return Cow::Borrowed(string.as_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(string.len() + 2);
+    for char in string.chars() {
+        match char {
+            '\0' => bytes.extend_from_slice(&[0xc0, 0x80]),
+            '\u{10000}'.. => {
+                for unit in char.encode_utf16(&mut [0; 2]) {
+                    bytes.extend_from_slice(&[
+                        0xe0 | (*unit >> 12) as u8,
+                        0x80 | (*unit >> 6 & 0x3f) as u8,
+                        0x80 | (*unit & 0x3f) as u8,
+                    ]);
+                }
+            }
+            _ => bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    Cow::Owned(bytes)
+}
+
+/// Decodes Java's modified UTF-8, also accepting plain UTF-8.
+pub fn decode_mutf8(bytes: &[u8]) -> Result<String> {
+    if let Ok(string) = std::str::from_utf8(bytes) {
+        return Ok(string.to_owned());
+    }
+
+    let continuation = |byte: Option<&u8>| match byte {
+        Some(byte) if byte & 0xc0 == 0x80 => Ok(u16::from(byte & 0x3f)),
+        _ => Err(invalid("invalid modified UTF-8")),
+    };
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter();
+    while let Some(&byte) = bytes.next() {
+        units.push(match byte {
+            0x00..=0x7f => byte.into(),
+            0xc0..=0xdf => u16::from(byte & 0x1f) << 6 | continuation(bytes.next())?,
+            0xe0..=0xef => {
+                u16::from(byte & 0x0f) << 12
+                    | continuation(bytes.next())? << 6
+                    | continuation(bytes.next())?
+            }
+            _ => return Err(invalid("invalid modified UTF-8")),
+        });
+    }
+
+    String::from_utf16(&units).map_err(|_| invalid("unpaired surrogate in modified UTF-8"))
+}
+
+/// The fewest bytes a payload of type `id` takes, so list lengths can be checked against
+/// what is left before looping over them.
+fn min_payload_length(id: u8) -> usize {
+    match id {
+        END => 0,
+        BYTE | COMPOUND => 1,
+        SHORT | STRING => 2,
+        INT | FLOAT | BYTE_ARRAY | INT_ARRAY | LONG_ARRAY => 4,
+        LIST => 5,
+        _ => 8,
+    }
+}
+
+/// Tracks how deep and how much a single root has decoded.
+struct Reader<'a, B> {
+    buf: &'a mut B,
+    limits: &'a NbtLimits,
+    size: usize,
+}
+
+impl<B: Buf> Reader<'_, B> {
+    fn charge(&mut self, bytes: usize) -> Result<()> {
+        self.size = self.size.saturating_add(bytes);
+        if self.size > self.limits.max_size {
+            return Err(Error::NbtTooLarge(self.limits.max_size));
+        }
+        Ok(())
+    }
+
+    fn length(&mut self, item_size: usize) -> Result<usize> {
+        let length = i32::decode(self.buf)?;
+        let length = usize::try_from(length).map_err(|_| Error::NegativeLength(length))?;
+
+        ensure_remaining(self.buf, length.saturating_mul(item_size))?;
+        self.charge(length.saturating_mul(item_size))?;
+        Ok(length)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let length = u16::decode(self.buf)?.into();
+        ensure_remaining(self.buf, length)?;
+        self.charge(length)?;
+
+        decode_mutf8(&self.buf.copy_to_bytes(length))
+    }
+
+    fn array<T>(&mut self, mut decode: impl FnMut(&mut B) -> Result<T>) -> Result<Vec<T>> {
+        let length = self.length(size_of::<T>())?;
+        (0..length).map(|_| decode(self.buf)).collect()
+    }
+
+    /// Decodes a tag that doesn't nest.
+    fn flat(&mut self, id: u8) -> Result<Tag> {
+        Ok(match id {
+            BYTE => Tag::Byte(i8::decode(self.buf)?),
+            SHORT => Tag::Short(i16::decode(self.buf)?),
+            INT => Tag::Int(i32::decode(self.buf)?),
+            LONG => Tag::Long(i64::decode(self.buf)?),
+            FLOAT => Tag::Float(f32::decode(self.buf)?),
+            DOUBLE => Tag::Double(f64::decode(self.buf)?),
+            BYTE_ARRAY => Tag::ByteArray(self.array(|buf| i8::decode(buf))?),
+            STRING => Tag::String(self.string()?),
+            INT_ARRAY => Tag::IntArray(self.array(|buf| i32::decode(buf))?),
+            LONG_ARRAY => Tag::LongArray(self.array(|buf| i64::decode(buf))?),
+            id => return Err(invalid(format!("unknown tag type {id}"))),
+        })
+    }
+
+    /// Decodes the payload of a compound, keeping the compounds and lists it is inside of on
+    /// a stack rather than recursing, so deep nesting can't overflow ours.
+    fn compound(&mut self) -> Result<Compound> {
+        enum Frame {
+            Compound(Compound),
+            List {
+                id: u8,
+                remaining: usize,
+                tags: Vec<Tag>,
+            },
+        }
+
+        impl Frame {
+            fn into_tag(self) -> Tag {
+                match self {
+                    Frame::Compound(compound) => Tag::Compound(compound),
+                    Frame::List { tags, .. } => Tag::List(tags),
+                }
+            }
+
+            fn add(&mut self, name: Option<String>, tag: Tag) {
+                match self {
+                    Frame::Compound(compound) => {
+                        compound.insert(name.expect("compound entries are named"), tag);
+                    }
+                    Frame::List { tags, .. } => tags.push(tag),
+                }
+            }
+        }
+
+        // each frame is kept with the name it goes under in the frame below
+        let mut stack = vec![(None, Frame::Compound(Compound::new()))];
+        while let Some((_, frame)) = stack.last_mut() {
+            let (name, id) = match frame {
+                Frame::Compound(_) => match u8::decode(self.buf)? {
+                    END => (None, END),
+                    id => (Some(self.string()?), id),
+                },
+                Frame::List { remaining: 0, .. } => (None, END),
+                Frame::List { id, remaining, .. } => {
+                    *remaining -= 1;
+                    (None, *id)
+                }
+            };
+
+            if id == END {
+                let (name, frame) = stack.pop().expect("the loop only runs with frames left");
+                match stack.last_mut() {
+                    Some((_, parent)) => parent.add(name, frame.into_tag()),
+                    None => match frame {
+                        Frame::Compound(compound) => return Ok(compound),
+                        Frame::List { .. } => unreachable!("the root is a compound"),
+                    },
+                }
+                continue;
+            }
+
+            self.charge(size_of::<Tag>())?;
+            let child = match id {
+                COMPOUND => Frame::Compound(Compound::new()),
+                LIST => {
+                    let id = u8::decode(self.buf)?;
+                    let remaining = self.length(min_payload_length(id))?;
+                    if id == END && remaining > 0 {
+                        return Err(invalid("list of TAG_End can't have elements"));
+                    }
+                    // not allocated up front, every tag is charged as it is decoded instead
+                    Frame::List {
+                        id,
+                        remaining,
+                        tags: vec![],
+                    }
+                }
+                id => {
+                    let tag = self.flat(id)?;
+                    frame.add(name, tag);
+                    continue;
+                }
+            };
+
+            if stack.len() >= self.limits.max_depth {
+                return Err(Error::NbtTooDeep(self.limits.max_depth));
+            }
+            stack.push((name, child));
+        }
+
+        unreachable!("the root compound returns when it ends")
+    }
+
+    fn root(&mut self) -> Result<()> {
+        match u8::decode(self.buf)? {
+            COMPOUND => Ok(()),
+            id => Err(invalid(format!(
+                "root must be a compound, got tag type {id}"
+            ))),
+        }
+    }
+}
+
+fn encode_string(string: &str, buf: &mut impl BufMut) -> Result<()> {
+    let bytes = encode_mutf8(string);
+    let length = u16::try_from(bytes.len()).map_err(|_| Error::StringTooLarge {
+        length: bytes.len(),
+        limit: u16::MAX.into(),
+    })?;
+
+    length.encode(buf)?;
+    buf.put_slice(&bytes);
+    Ok(())
+}
+
+fn encode_array<T: Encode>(values: &[T], buf: &mut impl BufMut) -> Result<()> {
+    let length = i32::try_from(values.len()).map_err(|_| Error::CollectionTooLarge {
+        length: values.len(),
+        max: i32::MAX as usize,
+    })?;
+
+    length.encode(buf)?;
+    values.iter().try_for_each(|value| value.encode(buf))
+}
+
+impl Tag {
+    /// Encodes the payload, without the type id or a name.
+    pub fn encode_payload(&self, buf: &mut impl BufMut) -> Result<()> {
+        match self {
+            Tag::Byte(value) => value.encode(buf),
+            Tag::Short(value) => value.encode(buf),
+            Tag::Int(value) => value.encode(buf),
+            Tag::Long(value) => value.encode(buf),
+            Tag::Float(value) => value.encode(buf),
+            Tag::Double(value) => value.encode(buf),
+            Tag::ByteArray(values) => encode_array(values, buf),
+            Tag::String(value) => encode_string(value, buf),
+            Tag::List(tags) => {
+                let id = tags.first().map_or(END, Tag::id);
+                if let Some(tag) = tags.iter().find(|tag| tag.id() != id) {
+                    return Err(invalid(format!(
+                        "list of tag type {id} can't hold a tag of type {}",
+                        tag.id()
+                    )));
+                }
+
+                id.encode(buf)?;
+                encode_array(tags, buf)
+            }
+            Tag::Compound(compound) => compound.encode_payload(buf),
+            Tag::IntArray(values) => encode_array(values, buf),
+            Tag::LongArray(values) => encode_array(values, buf),
+        }
+    }
+}
+
+impl Encode for Tag {
+    /// Encodes the payload only, as elements of lists are.
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.encode_payload(buf)
+    }
+}
+
+impl Compound {
+    /// Encodes the named tags and the `TAG_End` after them.
+    pub fn encode_payload(&self, buf: &mut impl BufMut) -> Result<()> {
+        for (name, tag) in &self.0 {
+            tag.id().encode(buf)?;
+            encode_string(name, buf)?;
+            tag.encode_payload(buf)?;
+        }
+        END.encode(buf)
+    }
+
+    /// Encodes this as a root compound called `name`, as files and 1.20.1 packets store it.
+    pub fn encode_named(&self, name: &str, buf: &mut impl BufMut) -> Result<()> {
+        COMPOUND.encode(buf)?;
+        encode_string(name, buf)?;
+        self.encode_payload(buf)
+    }
+
+    /// Encodes this as a root compound without a name, as packets send it since 1.20.2.
+    pub fn encode_network(&self, buf: &mut impl BufMut) -> Result<()> {
+        COMPOUND.encode(buf)?;
+        self.encode_payload(buf)
+    }
+
+    /// Decodes a named root compound, returning its name too.
+    pub fn decode_named(buf: &mut impl Buf, limits: &NbtLimits) -> Result<(String, Self)> {
+        let mut reader = Reader {
+            buf,
+            limits,
+            size: 0,
+        };
+        reader.root()?;
+        let name = reader.string()?;
+        Ok((name, reader.compound()?))
+    }
+
+    /// Decodes a root compound without a name.
+    pub fn decode_network(buf: &mut impl Buf, limits: &NbtLimits) -> Result<Self> {
+        let mut reader = Reader {
+            buf,
+            limits,
+            size: 0,
+        };
+        reader.root()?;
+        reader.compound()
+    }
+}
+
+/// Reads the bytes of one root compound, named or not, without decoding it, since its length
+/// is only known by walking it.
+///
+/// Nesting is tracked on a stack rather than by recursing, and the same limits apply as to
+/// decoding.
+pub async fn read_root_bytes(
+    reader: &mut impl ReadExt,
+    named: bool,
+    limits: &NbtLimits,
+) -> Result<Vec<u8>> {
+    enum Frame {
+        Compound,
+        List { id: u8, remaining: usize },
+    }
+
+    struct Walker<'a, R> {
+        reader: &'a mut R,
+        limits: &'a NbtLimits,
+        bytes: Vec<u8>,
+    }
+
+    impl<R: ReadExt> Walker<'_, R> {
+        async fn bytes(&mut self, length: usize) -> Result<&[u8]> {
+            if self.bytes.len().saturating_add(length) > self.limits.max_size {
+                return Err(Error::NbtTooLarge(self.limits.max_size));
+            }
+
+            let start = self.bytes.len();
+            (&mut *self.reader)
+                .take(length as u64)
+                .read_to_end(&mut self.bytes)
+                .await?;
+            if self.bytes.len() - start < length {
+                return Err(Error::Io(std::io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "Unexpected end of file",
+                )));
+            }
+
+            Ok(&self.bytes[start..])
+        }
+
+        async fn id(&mut self) -> Result<u8> {
+            Ok(self.bytes(1).await?[0])
+        }
+
+        async fn length(&mut self, item_size: usize) -> Result<usize> {
+            let length = i32::from_be_bytes(self.bytes(4).await?.try_into().unwrap());
+            let length = usize::try_from(length).map_err(|_| Error::NegativeLength(length))?;
+
+            if length.saturating_mul(item_size) > self.limits.max_size {
+                return Err(Error::NbtTooLarge(self.limits.max_size));
+            }
+            Ok(length)
+        }
+
+        async fn string(&mut self) -> Result<()> {
+            let length = u16::from_be_bytes(self.bytes(2).await?.try_into().unwrap());
+            self.bytes(length.into()).await?;
+            Ok(())
+        }
+    }
+
+    let mut copy = Walker {
+        reader,
+        limits,
+        bytes: vec![],
+    };
+
+    match copy.id().await? {
+        COMPOUND => {}
+        id => {
+            return Err(invalid(format!(
+                "root must be a compound, got tag type {id}"
+            )))
+        }
+    }
+    if named {
+        copy.string().await?;
+    }
+
+    let mut stack = vec![Frame::Compound];
+    while let Some(frame) = stack.last_mut() {
+        let id = match frame {
+            Frame::Compound => {
+                let id = copy.id().await?;
+                if id == END {
+                    stack.pop();
+                    continue;
+                }
+                copy.string().await?;
+                id
+            }
+            Frame::List { remaining: 0, .. } => {
+                stack.pop();
+                continue;
+            }
+            Frame::List { id, remaining } => {
+                *remaining -= 1;
+                *id
+            }
+        };
+
+        match id {
+            BYTE | SHORT | INT | LONG | FLOAT | DOUBLE => {
+                copy.bytes(min_payload_length(id)).await?;
+            }
+            BYTE_ARRAY | INT_ARRAY | LONG_ARRAY => {
+                let item_size = match id {
+                    BYTE_ARRAY => 1,
+                    INT_ARRAY => 4,
+                    _ => 8,
+                };
+                let length = copy.length(item_size).await?;
+                copy.bytes(length * item_size).await?;
+            }
+            STRING => copy.string().await?,
+            LIST => {
+                let id = copy.id().await?;
+                let remaining = copy.length(min_payload_length(id)).await?;
+                if id == END && remaining > 0 {
+                    return Err(invalid("list of TAG_End can't have elements"));
+                }
+                stack.push(Frame::List { id, remaining });
+            }
+            COMPOUND => stack.push(Frame::Compound),
+            id => return Err(invalid(format!("unknown tag type {id}"))),
+        }
+
+        if stack.len() > limits.max_depth {
+            return Err(Error::NbtTooDeep(limits.max_depth));
+        }
+    }
+
+    Ok(copy.bytes)
+}
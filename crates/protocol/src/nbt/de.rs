@@ -0,0 +1,316 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use serde::de::{
+    self,
+    value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer},
+    Deserialize, DeserializeOwned, IntoDeserializer, Visitor,
+};
+
+use super::*;
+
+/// Deserializes a `T` out of `tag`.
+pub fn from_tag<T: DeserializeOwned>(tag: Tag) -> Result<T> {
+    T::deserialize(tag)
+}
+
+/// Deserializes a `T`, usually a struct, out of `compound`.
+pub fn from_compound<T: DeserializeOwned>(compound: Compound) -> Result<T> {
+    from_tag(Tag::Compound(compound))
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error::Nbt(message.to_string())
+    }
+}
+
+/// Hands an array to a visitor that can't tell it apart from a list otherwise, as a map with
+/// the array's token as its only key.
+fn array_map(
+    token: &'static str,
+    tags: Vec<Tag>,
+) -> MapDeserializer<'static, std::iter::Once<(&'static str, Tag)>, Error> {
+    MapDeserializer::new(std::iter::once((token, Tag::List(tags))))
+}
+
+impl<'de> de::Deserializer<'de> for Tag {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::Byte(value) => visitor.visit_i8(value),
+            Tag::Short(value) => visitor.visit_i16(value),
+            Tag::Int(value) => visitor.visit_i32(value),
+            Tag::Long(value) => visitor.visit_i64(value),
+            Tag::Float(value) => visitor.visit_f32(value),
+            Tag::Double(value) => visitor.visit_f64(value),
+            Tag::ByteArray(values) => visitor.visit_map(array_map(
+                BYTE_ARRAY_TOKEN,
+                values.into_iter().map(Tag::Byte).collect(),
+            )),
+            Tag::String(value) => visitor.visit_string(value),
+            Tag::List(tags) => visitor.visit_seq(SeqDeserializer::new(tags.into_iter())),
+            Tag::Compound(compound) => {
+                visitor.visit_map(MapDeserializer::new(compound.into_iter()))
+            }
+            Tag::IntArray(values) => visitor.visit_map(array_map(
+                INT_ARRAY_TOKEN,
+                values.into_iter().map(Tag::Int).collect(),
+            )),
+            Tag::LongArray(values) => visitor.visit_map(array_map(
+                LONG_ARRAY_TOKEN,
+                values.into_iter().map(Tag::Long).collect(),
+            )),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::Byte(value) => visitor.visit_bool(value != 0),
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    // the reverse of how unsigned integers are serialized
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::Byte(value) => visitor.visit_u8(value as u8),
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::Short(value) => visitor.visit_u16(value as u16),
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::Int(value) => visitor.visit_u32(value as u32),
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::Long(value) => visitor.visit_u64(value as u64),
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::ByteArray(values) => {
+                visitor.visit_byte_buf(values.into_iter().map(|value| value as u8).collect())
+            }
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // missing fields are what's `None`, anything that is there is `Some`
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::ByteArray(values) => visitor.visit_seq(SeqDeserializer::new(values.into_iter())),
+            Tag::List(tags) => visitor.visit_seq(SeqDeserializer::new(tags.into_iter())),
+            Tag::IntArray(values) => visitor.visit_seq(SeqDeserializer::new(values.into_iter())),
+            Tag::LongArray(values) => visitor.visit_seq(SeqDeserializer::new(values.into_iter())),
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _length: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _length: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self {
+            Tag::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Tag::Compound(compound) if compound.len() == 1 => visitor.visit_enum(
+                MapAccessDeserializer::new(MapDeserializer::new(compound.into_iter())),
+            ),
+            tag => Err(Error::Nbt(format!(
+                "enums must be strings or compounds with a single key, got tag type {}",
+                tag.id()
+            ))),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u128 f32 f64 char str string map struct identifier
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Tag {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct TagVisitor;
+
+impl<'de> Visitor<'de> for TagVisitor {
+    type Value = Tag;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an NBT tag")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Tag, E> {
+        Ok(value.into())
+    }
+
+    fn visit_i8<E>(self, value: i8) -> Result<Tag, E> {
+        Ok(value.into())
+    }
+
+    fn visit_i16<E>(self, value: i16) -> Result<Tag, E> {
+        Ok(value.into())
+    }
+
+    fn visit_i32<E>(self, value: i32) -> Result<Tag, E> {
+        Ok(value.into())
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Tag, E> {
+        Ok(value.into())
+    }
+
+    // widened rather than wrapped, since other formats don't have NBT's conventions
+
+    fn visit_u8<E>(self, value: u8) -> Result<Tag, E> {
+        Ok(Tag::Short(value.into()))
+    }
+
+    fn visit_u16<E>(self, value: u16) -> Result<Tag, E> {
+        Ok(Tag::Int(value.into()))
+    }
+
+    fn visit_u32<E>(self, value: u32) -> Result<Tag, E> {
+        Ok(Tag::Long(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Tag, E> {
+        Ok(Tag::Long(value as i64))
+    }
+
+    fn visit_f32<E>(self, value: f32) -> Result<Tag, E> {
+        Ok(value.into())
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Tag, E> {
+        Ok(value.into())
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Tag, E> {
+        Ok(value.into())
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Tag, E> {
+        Ok(value.into())
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Tag, E> {
+        Ok(Tag::ByteArray(
+            value.iter().map(|&byte| byte as i8).collect(),
+        ))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Tag, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Tag, A::Error> {
+        let mut tags = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(tag) = seq.next_element()? {
+            tags.push(tag);
+        }
+        Ok(Tag::List(tags))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Tag, A::Error> {
+        let mut compound = Compound::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                BYTE_ARRAY_TOKEN if compound.is_empty() => {
+                    return Ok(Tag::ByteArray(map.next_value()?));
+                }
+                INT_ARRAY_TOKEN if compound.is_empty() => {
+                    return Ok(Tag::IntArray(map.next_value()?));
+                }
+                LONG_ARRAY_TOKEN if compound.is_empty() => {
+                    return Ok(Tag::LongArray(map.next_value()?));
+                }
+                _ => {
+                    let tag = map.next_value()?;
+                    compound.insert(key, tag);
+                }
+            }
+        }
+
+        Ok(Tag::Compound(compound))
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(TagVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Compound {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BTreeMap::deserialize(deserializer).map(Self)
+    }
+}
@@ -0,0 +1,90 @@
+use async_compression::futures::{
+    bufread::{GzipDecoder, ZlibDecoder},
+    write::{GzipEncoder, ZlibEncoder},
+};
+
+use super::*;
+
+/// How an NBT file is compressed. Vanilla gzips `level.dat` and player data, and zlibs the
+/// chunks in region files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NbtCompression {
+    None,
+    #[default]
+    Gzip,
+    Zlib,
+}
+
+impl NbtCompression {
+    /// Tells the compression apart by the first bytes of `data`, as a root compound always
+    /// starts with `0x0a`.
+    pub fn detect(data: &[u8]) -> Self {
+        match data {
+            [0x1f, 0x8b, ..] => Self::Gzip,
+            [0x78, ..] => Self::Zlib,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Reads at most `limits.max_size` bytes, failing if there are more.
+async fn read_limited(reader: impl Read + Unpin, limits: &NbtLimits) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    reader
+        .take(limits.max_size as u64 + 1)
+        .read_to_end(&mut data)
+        .await?;
+
+    if data.len() > limits.max_size {
+        return Err(Error::NbtTooLarge(limits.max_size));
+    }
+    Ok(data)
+}
+
+/// Reads the named root compound of an NBT file, however it is compressed.
+pub async fn read_file(
+    reader: &mut impl ReadExt,
+    limits: &NbtLimits,
+) -> Result<(String, Compound)> {
+    let raw = read_limited(reader, limits).await?;
+    let data = match NbtCompression::detect(&raw) {
+        NbtCompression::None => raw,
+        NbtCompression::Gzip => read_limited(GzipDecoder::new(&raw[..]), limits).await?,
+        NbtCompression::Zlib => read_limited(ZlibDecoder::new(&raw[..]), limits).await?,
+    };
+
+    Compound::decode_named(&mut &data[..], limits)
+}
+
+/// Writes `root` as an NBT file with a root called `name`.
+pub async fn write_file(
+    writer: &mut impl WriteExt,
+    name: &str,
+    root: &Compound,
+    compression: NbtCompression,
+) -> Result<()> {
+    use futures::AsyncWriteExt;
+
+    let mut data = BytesMut::new();
+    root.encode_named(name, &mut data)?;
+
+    // closing the encoders writes out the end of the stream, so they compress into a buffer
+    // rather than into `writer`
+    let data = match compression {
+        NbtCompression::None => data.to_vec(),
+        NbtCompression::Gzip => {
+            let mut encoder = GzipEncoder::new(vec![]);
+            write_rest(&mut encoder, &data).await?;
+            encoder.close().await?;
+            encoder.into_inner()
+        }
+        NbtCompression::Zlib => {
+            let mut encoder = ZlibEncoder::new(vec![]);
+            write_rest(&mut encoder, &data).await?;
+            encoder.close().await?;
+            encoder.into_inner()
+        }
+    };
+
+    write_rest(writer, &data).await
+}
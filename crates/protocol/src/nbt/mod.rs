@@ -0,0 +1,316 @@
+//! Named Binary Tag, the format of Minecraft's save files and of structured data in packets
+//! such as the registry codec, chunk heightmaps and item tags.
+//!
+//! [`Tag`] and [`Compound`] hold any NBT value, and [`to_compound`] and [`from_compound`]
+//! convert them from and to typed structs through serde. [`Nbt`] puts such a struct in a
+//! packet.
+
+use std::{
+    collections::BTreeMap,
+    ops::{Deref, DerefMut},
+};
+
+use super::*;
+
+mod binary;
+mod de;
+mod file;
+mod ser;
+mod snbt;
+
+pub use binary::*;
+pub use de::*;
+pub use file::*;
+pub use ser::*;
+pub use snbt::*;
+
+pub const END: u8 = 0;
+pub const BYTE: u8 = 1;
+pub const SHORT: u8 = 2;
+pub const INT: u8 = 3;
+pub const LONG: u8 = 4;
+pub const FLOAT: u8 = 5;
+pub const DOUBLE: u8 = 6;
+pub const BYTE_ARRAY: u8 = 7;
+pub const STRING: u8 = 8;
+pub const LIST: u8 = 9;
+pub const COMPOUND: u8 = 10;
+pub const INT_ARRAY: u8 = 11;
+pub const LONG_ARRAY: u8 = 12;
+
+/// Any NBT value but `TAG_End`, which only marks the end of a compound.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// All elements must be of the same type, which encoding checks.
+    List(Vec<Tag>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// The type id this tag is encoded with.
+    pub fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => BYTE,
+            Tag::Short(_) => SHORT,
+            Tag::Int(_) => INT,
+            Tag::Long(_) => LONG,
+            Tag::Float(_) => FLOAT,
+            Tag::Double(_) => DOUBLE,
+            Tag::ByteArray(_) => BYTE_ARRAY,
+            Tag::String(_) => STRING,
+            Tag::List(_) => LIST,
+            Tag::Compound(_) => COMPOUND,
+            Tag::IntArray(_) => INT_ARRAY,
+            Tag::LongArray(_) => LONG_ARRAY,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&Compound> {
+        match self {
+            Tag::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Any integer tag widened to an `i64`, with booleans being bytes.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(value) => Some(value.into()),
+            Tag::Short(value) => Some(value.into()),
+            Tag::Int(value) => Some(value.into()),
+            Tag::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Tag::Float(value) => Some(value.into()),
+            Tag::Double(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_from_for_tag {
+    ($($ty:ty => $variant:ident),*$(,)?) => {
+        $(
+        impl From<$ty> for Tag {
+            fn from(value: $ty) -> Self {
+                Tag::$variant(value.into())
+            }
+        }
+        )*
+    };
+}
+
+impl_from_for_tag! {
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    Vec<i8> => ByteArray,
+    String => String,
+    &str => String,
+    Vec<Tag> => List,
+    Compound => Compound,
+    Vec<i32> => IntArray,
+    Vec<i64> => LongArray,
+}
+
+impl From<bool> for Tag {
+    fn from(value: bool) -> Self {
+        Tag::Byte(value.into())
+    }
+}
+
+// serde has no notion of NBT's arrays, so they pass through it as newtype structs with these
+// names, or as single entry maps keyed by them where only `deserialize_any` is called
+const BYTE_ARRAY_TOKEN: &str = "__nbt_byte_array";
+const INT_ARRAY_TOKEN: &str = "__nbt_int_array";
+const LONG_ARRAY_TOKEN: &str = "__nbt_long_array";
+
+macro_rules! array_module {
+    ($(#[$attr:meta])* $module:ident, $ty:ty, $token:ident) => {
+        $(#[$attr])*
+        ///
+        /// Use it as `#[serde(with = "...")]` on a field, other formats see a plain sequence.
+        pub mod $module {
+            use serde::{de, Deserialize, Deserializer, Serializer};
+
+            pub fn serialize<S: Serializer>(values: &[$ty], serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_newtype_struct(super::$token, values)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Vec<$ty>, D::Error> {
+                struct ArrayVisitor;
+
+                impl<'de> de::Visitor<'de> for ArrayVisitor {
+                    type Value = Vec<$ty>;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        formatter.write_str("an NBT array")
+                    }
+
+                    fn visit_newtype_struct<D: Deserializer<'de>>(
+                        self,
+                        deserializer: D,
+                    ) -> Result<Self::Value, D::Error> {
+                        Vec::deserialize(deserializer)
+                    }
+                }
+
+                deserializer.deserialize_newtype_struct(super::$token, ArrayVisitor)
+            }
+        }
+    };
+}
+
+array_module!(
+    /// Serializes a `Vec<i8>` as a `TAG_Byte_Array` rather than a list of bytes.
+    byte_array,
+    i8,
+    BYTE_ARRAY_TOKEN
+);
+array_module!(
+    /// Serializes a `Vec<i32>` as a `TAG_Int_Array` rather than a list of ints.
+    int_array,
+    i32,
+    INT_ARRAY_TOKEN
+);
+array_module!(
+    /// Serializes a `Vec<i64>` as a `TAG_Long_Array` rather than a list of longs.
+    long_array,
+    i64,
+    LONG_ARRAY_TOKEN
+);
+
+/// Named tags, kept sorted by name so encoding is deterministic.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Compound(pub BTreeMap<String, Tag>);
+
+impl Compound {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `value` under `name`, for building compounds in one expression.
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<Tag>) -> Self {
+        self.0.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl Deref for Compound {
+    type Target = BTreeMap<String, Tag>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Compound {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<K: Into<String>, V: Into<Tag>> FromIterator<(K, V)> for Compound {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl IntoIterator for Compound {
+    type Item = (String, Tag);
+    type IntoIter = std::collections::btree_map::IntoIter<String, Tag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// How much NBT from an untrusted source may nest and allocate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NbtLimits {
+    /// Compounds and lists nested deeper than this are rejected.
+    pub max_depth: usize,
+    /// Rough number of bytes decoding may allocate, counting both payloads and per-tag
+    /// overhead.
+    pub max_size: usize,
+}
+
+impl NbtLimits {
+    /// What vanilla allows.
+    pub const MAX_DEPTH: usize = 512;
+    /// What vanilla allows for NBT in packets.
+    pub const MAX_SIZE: usize = 2 * 1024 * 1024;
+}
+
+impl Default for NbtLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: Self::MAX_DEPTH,
+            max_size: Self::MAX_SIZE,
+        }
+    }
+}
+
+/// A value sent as NBT in a packet: a root compound with an empty name, as 1.20.1 expects.
+///
+/// `T` is anything serde can turn into a compound, including [`Compound`] itself.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Nbt<T = Compound>(pub T);
+
+impl<T: serde::Serialize> Encode for Nbt<T> {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        to_compound(&self.0)?.encode_named("", buf)
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> Decode for Nbt<T> {
+    fn decode(buf: &mut impl Buf) -> Result<Self> {
+        let (_, root) = Compound::decode_named(buf, &NbtLimits::default())?;
+        from_compound(root).map(Self)
+    }
+}
+
+impl<T: serde::Serialize> AsyncSerialize for Nbt<T> {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        let mut data = BytesMut::new();
+        self.encode(&mut data)?;
+        write_rest(writer, &data).await
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> AsyncDeserialize for Nbt<T> {
+    async fn read_from(reader: &mut impl ReadExt) -> Result<Self> {
+        let data = read_root_bytes(reader, true, &NbtLimits::default()).await?;
+        Self::decode(&mut &data[..])
+    }
+}
@@ -0,0 +1,394 @@
+use std::fmt::Display;
+
+use serde::ser::{self, Serialize};
+
+use super::*;
+
+/// Serializes `value` into a tag.
+pub fn to_tag<T: Serialize + ?Sized>(value: &T) -> Result<Tag> {
+    value
+        .serialize(TagSerializer)?
+        .ok_or_else(|| Error::Nbt(String::from("can't serialize a missing value")))
+}
+
+/// Serializes `value` into a compound, so it has to be a struct or a map.
+pub fn to_compound<T: Serialize + ?Sized>(value: &T) -> Result<Compound> {
+    match to_tag(value)? {
+        Tag::Compound(compound) => Ok(compound),
+        tag => Err(Error::Nbt(format!(
+            "expected a compound, got tag type {}",
+            tag.id()
+        ))),
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error::Nbt(message.to_string())
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Tag::Byte(value) => serializer.serialize_i8(*value),
+            Tag::Short(value) => serializer.serialize_i16(*value),
+            Tag::Int(value) => serializer.serialize_i32(*value),
+            Tag::Long(value) => serializer.serialize_i64(*value),
+            Tag::Float(value) => serializer.serialize_f32(*value),
+            Tag::Double(value) => serializer.serialize_f64(*value),
+            Tag::ByteArray(values) => serializer.serialize_newtype_struct(BYTE_ARRAY_TOKEN, values),
+            Tag::String(value) => serializer.serialize_str(value),
+            Tag::List(tags) => tags.serialize(serializer),
+            Tag::Compound(compound) => compound.serialize(serializer),
+            Tag::IntArray(values) => serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, values),
+            Tag::LongArray(values) => serializer.serialize_newtype_struct(LONG_ARRAY_TOKEN, values),
+        }
+    }
+}
+
+impl Serialize for Compound {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Turns a list that was serialized in place of an array back into one.
+fn list_into_array<T>(tag: Option<Tag>, element: fn(Tag) -> Option<T>) -> Result<Vec<T>> {
+    match tag {
+        Some(Tag::List(tags)) => tags
+            .into_iter()
+            .map(|tag| {
+                element(tag).ok_or_else(|| Error::Nbt(String::from("mixed types in an array")))
+            })
+            .collect(),
+        _ => Err(Error::Nbt(String::from("arrays must be sequences"))),
+    }
+}
+
+/// Serializes to `None` for values that leave out their field, such as `None` itself.
+struct TagSerializer;
+
+impl ser::Serializer for TagSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeVariant<SerializeList>;
+    type SerializeMap = SerializeCompound;
+    type SerializeStruct = SerializeCompound;
+    type SerializeStructVariant = SerializeVariant<SerializeCompound>;
+
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok> {
+        Ok(Some(value.into()))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Self::Ok> {
+        Ok(Some(value.into()))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Self::Ok> {
+        Ok(Some(value.into()))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Self::Ok> {
+        Ok(Some(value.into()))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Self::Ok> {
+        Ok(Some(value.into()))
+    }
+
+    // NBT has no unsigned types, so these keep their width and wrap like Java's do
+
+    fn serialize_u8(self, value: u8) -> Result<Self::Ok> {
+        Ok(Some(Tag::Byte(value as i8)))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Self::Ok> {
+        Ok(Some(Tag::Short(value as i16)))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Self::Ok> {
+        Ok(Some(Tag::Int(value as i32)))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Self::Ok> {
+        Ok(Some(Tag::Long(value as i64)))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Self::Ok> {
+        Ok(Some(value.into()))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Self::Ok> {
+        Ok(Some(value.into()))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Self::Ok> {
+        Ok(Some(Tag::String(value.into())))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok> {
+        Ok(Some(value.into()))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok> {
+        Ok(Some(Tag::ByteArray(
+            value.iter().map(|&byte| byte as i8).collect(),
+        )))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Ok(Some(Compound::new().into()))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Ok(Some(variant.into()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        let tag = value.serialize(self)?;
+
+        Ok(Some(match name {
+            BYTE_ARRAY_TOKEN => Tag::ByteArray(list_into_array(tag, |tag| match tag {
+                Tag::Byte(value) => Some(value),
+                _ => None,
+            })?),
+            INT_ARRAY_TOKEN => Tag::IntArray(list_into_array(tag, |tag| match tag {
+                Tag::Int(value) => Some(value),
+                _ => None,
+            })?),
+            LONG_ARRAY_TOKEN => Tag::LongArray(list_into_array(tag, |tag| match tag {
+                Tag::Long(value) => Some(value),
+                _ => None,
+            })?),
+            _ => return Ok(tag),
+        }))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        Ok(Some(Compound::new().with(variant, to_tag(value)?).into()))
+    }
+
+    fn serialize_seq(self, length: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(SerializeList(Vec::with_capacity(length.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, length: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        length: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        length: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(SerializeVariant {
+            variant,
+            inner: SerializeList(Vec::with_capacity(length)),
+        })
+    }
+
+    fn serialize_map(self, _length: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(SerializeCompound::default())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _length: usize,
+    ) -> Result<Self::SerializeStruct> {
+        Ok(SerializeCompound::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _length: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(SerializeVariant {
+            variant,
+            inner: SerializeCompound::default(),
+        })
+    }
+}
+
+struct SerializeList(Vec<Tag>);
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.0.push(to_tag(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(Tag::List(self.0)))
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+#[derive(Default)]
+struct SerializeCompound {
+    compound: Compound,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeCompound {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        match to_tag(key)? {
+            Tag::String(key) => self.key = Some(key),
+            tag => {
+                return Err(Error::Nbt(format!(
+                    "compound keys must be strings, got tag type {}",
+                    tag.id()
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        if let Some(tag) = value.serialize(TagSerializer)? {
+            self.compound.insert(key, tag);
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.compound.into()))
+    }
+}
+
+impl ser::SerializeStruct for SerializeCompound {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        if let Some(tag) = value.serialize(TagSerializer)? {
+            self.compound.insert(name.into(), tag);
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Wraps what `inner` serializes in a compound with the variant's name as its only key.
+struct SerializeVariant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeList> {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        let list = Tag::List(self.inner.0);
+        Ok(Some(Compound::new().with(self.variant, list).into()))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeCompound> {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, name, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        let compound = self.inner.compound;
+        Ok(Some(Compound::new().with(self.variant, compound).into()))
+    }
+}
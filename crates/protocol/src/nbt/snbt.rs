@@ -0,0 +1,362 @@
+use std::{
+    fmt::{self, Write as _},
+    str::FromStr,
+};
+
+use super::*;
+
+/// Parses stringified NBT, as written in commands: `{name:"Steve",pos:[I;1,2,3],score:10L}`.
+pub fn parse_snbt(input: &str, limits: &NbtLimits) -> Result<Tag> {
+    let mut parser = Parser {
+        input,
+        position: 0,
+        limits,
+    };
+
+    let tag = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.position < input.len() {
+        return Err(parser.error("trailing characters"));
+    }
+
+    Ok(tag)
+}
+
+impl FromStr for Tag {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        parse_snbt(input, &NbtLimits::default())
+    }
+}
+
+impl FromStr for Compound {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.parse()? {
+            Tag::Compound(compound) => Ok(compound),
+            tag => Err(Error::Nbt(format!(
+                "expected a compound, got tag type {}",
+                tag.id()
+            ))),
+        }
+    }
+}
+
+/// Characters that don't need quotes, in keys and in strings that aren't numbers.
+fn is_unquoted(char: char) -> bool {
+    char.is_ascii_alphanumeric() || matches!(char, '_' | '-' | '.' | '+')
+}
+
+/// An optionally signed integer without leading zeroes.
+fn is_integer(token: &str) -> bool {
+    let digits = token.strip_prefix(['-', '+']).unwrap_or(token);
+
+    !digits.is_empty()
+        && digits.bytes().all(|byte| byte.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'))
+}
+
+/// An optionally signed decimal number with an optional exponent, which without a suffix
+/// needs a point to not be an integer.
+fn is_decimal(token: &str, needs_point: bool) -> bool {
+    let token = token.strip_prefix(['-', '+']).unwrap_or(token);
+    let (mantissa, exponent) = match token.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (token, None),
+    };
+
+    let digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    let mantissa_valid = match mantissa.split_once('.') {
+        Some((whole, fraction)) => {
+            digits(whole) && digits(fraction) && !(whole.is_empty() && fraction.is_empty())
+        }
+        None => !needs_point && !mantissa.is_empty() && digits(mantissa),
+    };
+    let exponent_valid = exponent.is_none_or(|exponent| {
+        let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+        !exponent.is_empty() && digits(exponent)
+    });
+
+    mantissa_valid && exponent_valid
+}
+
+/// Reads an unquoted token like vanilla does, falling back to a string when it isn't a number
+/// that fits its type.
+fn literal(token: &str) -> Tag {
+    match token {
+        "true" => return Tag::Byte(1),
+        "false" => return Tag::Byte(0),
+        _ => {}
+    }
+
+    let (body, suffix) = match token.char_indices().last() {
+        Some((index, suffix)) if index > 0 && "bBsSlLfFdD".contains(suffix) => {
+            (&token[..index], Some(suffix.to_ascii_lowercase()))
+        }
+        _ => (token, None),
+    };
+
+    let tag = match suffix {
+        Some('b') if is_integer(body) => body.parse().ok().map(Tag::Byte),
+        Some('s') if is_integer(body) => body.parse().ok().map(Tag::Short),
+        Some('l') if is_integer(body) => body.parse().ok().map(Tag::Long),
+        Some('f') if is_decimal(body, false) => body.parse().ok().map(Tag::Float),
+        Some('d') if is_decimal(body, false) => body.parse().ok().map(Tag::Double),
+        _ if is_integer(token) => token.parse().ok().map(Tag::Int),
+        _ if is_decimal(token, true) => token.parse().ok().map(Tag::Double),
+        _ => None,
+    };
+
+    tag.unwrap_or_else(|| Tag::String(token.to_owned()))
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    limits: &'a NbtLimits,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> Error {
+        Error::Nbt(format!("{message} at position {}", self.position))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips `char` and any whitespace before it if it's next.
+    fn eat(&mut self, char: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(char) {
+            self.position += char.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, char: char) -> Result<()> {
+        if !self.eat(char) {
+            return Err(self.error(&format!("expected '{char}'")));
+        }
+        Ok(())
+    }
+
+    fn check_depth(&self, depth: usize) -> Result<()> {
+        if depth > self.limits.max_depth {
+            return Err(Error::NbtTooDeep(self.limits.max_depth));
+        }
+        Ok(())
+    }
+
+    fn unquoted(&mut self) -> &'a str {
+        let rest = &self.input[self.position..];
+        let length = rest.find(|char| !is_unquoted(char)).unwrap_or(rest.len());
+        self.position += length;
+        &rest[..length]
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let quote = self.peek().expect("quoted strings start with a quote");
+        self.position += 1;
+
+        let mut string = String::new();
+        let mut chars = self.input[self.position..].char_indices();
+        while let Some((index, char)) = chars.next() {
+            match char {
+                '\\' => match chars.next() {
+                    Some((_, escaped @ ('\\' | '"' | '\''))) => string.push(escaped),
+                    _ => {
+                        self.position += index;
+                        return Err(self.error("invalid escape"));
+                    }
+                },
+                char if char == quote => {
+                    self.position += index + 1;
+                    return Ok(string);
+                }
+                char => string.push(char),
+            }
+        }
+
+        self.position = self.input.len();
+        Err(self.error("unterminated string"))
+    }
+
+    fn key(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"' | '\'') => self.quoted(),
+            _ => match self.unquoted() {
+                "" => Err(self.error("expected a key")),
+                key => Ok(key.to_owned()),
+            },
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Tag> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => Ok(Tag::Compound(self.compound(depth + 1)?)),
+            Some('[') => self.list_or_array(depth + 1),
+            Some('"' | '\'') => Ok(Tag::String(self.quoted()?)),
+            _ => match self.unquoted() {
+                "" => Err(self.error("expected a value")),
+                token => Ok(literal(token)),
+            },
+        }
+    }
+
+    fn compound(&mut self, depth: usize) -> Result<Compound> {
+        self.check_depth(depth)?;
+        self.expect('{')?;
+
+        let mut compound = Compound::new();
+        if self.eat('}') {
+            return Ok(compound);
+        }
+        loop {
+            let key = self.key()?;
+            self.expect(':')?;
+            let value = self.value(depth)?;
+            compound.insert(key, value);
+
+            if !self.eat(',') {
+                self.expect('}')?;
+                return Ok(compound);
+            }
+        }
+    }
+
+    /// Reads comma separated values up to the closing bracket.
+    fn elements(&mut self, depth: usize) -> Result<Vec<Tag>> {
+        let mut tags = vec![];
+        if self.eat(']') {
+            return Ok(tags);
+        }
+        loop {
+            tags.push(self.value(depth)?);
+
+            if !self.eat(',') {
+                self.expect(']')?;
+                return Ok(tags);
+            }
+        }
+    }
+
+    fn list_or_array(&mut self, depth: usize) -> Result<Tag> {
+        self.check_depth(depth)?;
+        self.expect('[')?;
+
+        let rest = &self.input[self.position..];
+        let array_id = match rest.get(..2) {
+            Some("B;") => Some(BYTE),
+            Some("I;") => Some(INT),
+            Some("L;") => Some(LONG),
+            _ => None,
+        };
+
+        let Some(array_id) = array_id else {
+            let tags = self.elements(depth)?;
+            if let Some(id) = tags.first().map(Tag::id) {
+                if tags.iter().any(|tag| tag.id() != id) {
+                    return Err(self.error("mixed tag types in a list"));
+                }
+            }
+            return Ok(Tag::List(tags));
+        };
+
+        self.position += 2;
+        let tags = self.elements(depth)?;
+        if tags.iter().any(|tag| tag.id() != array_id) {
+            return Err(self.error("wrong tag type in an array"));
+        }
+
+        let values = tags.iter().filter_map(Tag::as_i64);
+        Ok(match array_id {
+            BYTE => Tag::ByteArray(values.map(|value| value as i8).collect()),
+            INT => Tag::IntArray(values.map(|value| value as i32).collect()),
+            _ => Tag::LongArray(values.collect()),
+        })
+    }
+}
+
+/// Writes `string` in double quotes, or single ones if that needs fewer escapes.
+fn write_quoted(f: &mut fmt::Formatter, string: &str) -> fmt::Result {
+    let quote = if string.contains('"') && !string.contains('\'') {
+        '\''
+    } else {
+        '"'
+    };
+
+    f.write_char(quote)?;
+    for char in string.chars() {
+        if char == quote || char == '\\' {
+            f.write_char('\\')?;
+        }
+        f.write_char(char)?;
+    }
+    f.write_char(quote)
+}
+
+fn write_array<T: fmt::Display>(
+    f: &mut fmt::Formatter,
+    prefix: &str,
+    values: &[T],
+    suffix: &str,
+) -> fmt::Result {
+    write!(f, "[{prefix}")?;
+    for (index, value) in values.iter().enumerate() {
+        if index > 0 {
+            f.write_char(',')?;
+        }
+        write!(f, "{value}{suffix}")?;
+    }
+    f.write_char(']')
+}
+
+/// Prints SNBT that [`parse_snbt`] reads back into an equal tag.
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tag::Byte(value) => write!(f, "{value}b"),
+            Tag::Short(value) => write!(f, "{value}s"),
+            Tag::Int(value) => write!(f, "{value}"),
+            Tag::Long(value) => write!(f, "{value}L"),
+            Tag::Float(value) => write!(f, "{value}f"),
+            Tag::Double(value) => write!(f, "{value}d"),
+            Tag::ByteArray(values) => write_array(f, "B;", values, "b"),
+            Tag::String(value) => write_quoted(f, value),
+            Tag::List(tags) => write_array(f, "", tags, ""),
+            Tag::Compound(compound) => compound.fmt(f),
+            Tag::IntArray(values) => write_array(f, "I;", values, ""),
+            Tag::LongArray(values) => write_array(f, "L;", values, "L"),
+        }
+    }
+}
+
+impl fmt::Display for Compound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_char('{')?;
+        for (index, (name, tag)) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_char(',')?;
+            }
+            if !name.is_empty() && name.chars().all(is_unquoted) {
+                f.write_str(name)?;
+            } else {
+                write_quoted(f, name)?;
+            }
+            write!(f, ":{tag}")?;
+        }
+        f.write_char('}')
+    }
+}
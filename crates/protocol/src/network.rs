@@ -0,0 +1,276 @@
+use std::{
+    net::Shutdown,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_std::{
+    channel::{self, Receiver, Sender, TrySendError},
+    io::BufWriter,
+};
+
+use super::*;
+
+/// How many frames may queue up for a client that reads slower than it is sent to, and how
+/// often they are flushed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SendQueueLimits {
+    /// Frames that may wait for the writer before the queue is full.
+    pub capacity: usize,
+    /// How long [`PacketSender::send`] waits for room in a full queue before giving up on the
+    /// client.
+    pub max_wait: Duration,
+    /// How long written frames may wait before they are flushed, so everything sent in one
+    /// tick goes out together.
+    pub flush_interval: Duration,
+}
+
+impl SendQueueLimits {
+    pub const CAPACITY: usize = 4096;
+    pub const MAX_WAIT: Duration = Duration::from_secs(10);
+    /// One game tick.
+    pub const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+}
+
+impl Default for SendQueueLimits {
+    fn default() -> Self {
+        Self {
+            capacity: Self::CAPACITY,
+            max_wait: Self::MAX_WAIT,
+            flush_interval: Self::FLUSH_INTERVAL,
+        }
+    }
+}
+
+impl PlayerNetwork {
+    /// Splits the connection into a reader, a sender that can be cloned into any task, and
+    /// the writer that has to be run for what is sent to reach the client.
+    pub fn split(self, limits: SendQueueLimits) -> (PacketReader, PacketSender, PacketWriter) {
+        let (frames, queue) = channel::bounded(limits.capacity);
+        let compression_threshold = self.receive_threshold();
+
+        let reader = PacketReader {
+            tcp: self.tcp.clone(),
+            state: self.state,
+            compression_threshold,
+            frame_limits: self.frame_limits,
+            decode_budget: self.decode_budget,
+        };
+        let sender = PacketSender {
+            frames,
+            tcp: self.tcp.get_ref().clone(),
+            compressing: self.compressing,
+            compression_threshold: self.compression_threshold,
+            limits,
+        };
+        let writer = PacketWriter {
+            tcp: BufWriter::new(self.tcp),
+            queue,
+            flush_interval: limits.flush_interval,
+        };
+
+        (reader, sender, writer)
+    }
+}
+
+/// The half of a split [`PlayerNetwork`] that receives packets.
+pub struct PacketReader {
+    tcp: CipherStream<TcpStream>,
+    pub state: State,
+    compression_threshold: Option<usize>,
+    frame_limits: FrameLimits,
+    decode_budget: DecodeBudget,
+}
+
+impl PacketReader {
+    pub async fn recv_packet<T: DecodeContextful<Context = PacketContext>>(&mut self) -> Result<T> {
+        SerializedPacket::read_packet(
+            &mut self.tcp,
+            self.compression_threshold,
+            &self.frame_limits,
+            &mut self.decode_budget,
+            self.state,
+        )
+        .await
+    }
+}
+
+/// Queues packets for a [`PacketWriter`], encoding them in the calling task.
+///
+/// Clones share the queue, and sending never waits on the socket, only on room in the queue.
+#[derive(Clone)]
+pub struct PacketSender {
+    frames: Sender<Bytes>,
+    // to cut off a client whose queue overflowed without waiting for the writer
+    tcp: TcpStream,
+    compressing: bool,
+    compression_threshold: Option<usize>,
+    limits: SendQueueLimits,
+}
+
+impl PacketSender {
+    /// Encodes `packet` into a frame for this connection.
+    pub fn encode<T: Encode + Packet>(&self, packet: &T) -> Result<Bytes> {
+        SerializedPacket::encode_packet(packet, self.compressing, self.compression_threshold)
+    }
+
+    /// Queues `packet`, waiting while the queue is full. If it stays full for longer than
+    /// [`SendQueueLimits::max_wait`] the client is disconnected.
+    pub async fn send<T: Encode + Packet>(&self, packet: T) -> Result<()> {
+        self.send_frame(self.encode(&packet)?).await
+    }
+
+    /// Queues `packet`, disconnecting the client if the queue is full.
+    pub fn try_send<T: Encode + Packet>(&self, packet: T) -> Result<()> {
+        self.try_send_frame(self.encode(&packet)?)
+    }
+
+    /// Like [`PacketSender::send`], for a packet that is already serialized.
+    pub async fn send_encoded(&self, packet: &EncodedPacket) -> Result<()> {
+        self.send_frame(packet.frame(self.compressing, self.compression_threshold)?)
+            .await
+    }
+
+    /// Like [`PacketSender::try_send`], for a packet that is already serialized.
+    pub fn try_send_encoded(&self, packet: &EncodedPacket) -> Result<()> {
+        self.try_send_frame(packet.frame(self.compressing, self.compression_threshold)?)
+    }
+
+    /// Like [`PacketSender::send`], for a frame that is already encoded.
+    pub async fn send_frame(&self, frame: Bytes) -> Result<()> {
+        match async_std::future::timeout(self.limits.max_wait, self.frames.send(frame)).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(_)) => Err(Error::ConnectionClosed),
+            Err(_) => Err(self.overflow()),
+        }
+    }
+
+    /// Like [`PacketSender::try_send`], for a frame that is already encoded.
+    pub fn try_send_frame(&self, frame: Bytes) -> Result<()> {
+        match self.frames.try_send(frame) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Closed(_)) => Err(Error::ConnectionClosed),
+            Err(TrySendError::Full(_)) => Err(self.overflow()),
+        }
+    }
+
+    /// Lets the writer send what is already queued, then close the connection.
+    pub fn close(&self) {
+        self.frames.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.frames.is_closed()
+    }
+
+    /// How many frames are waiting for the writer.
+    pub fn queued(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn overflow(&self) -> Error {
+        // the client is too far behind for what's queued to be worth sending
+        self.frames.close();
+        let _ = self.tcp.shutdown(Shutdown::Both);
+
+        Error::SendQueueFull(self.limits.capacity)
+    }
+}
+
+/// A packet serialized once, to be sent to many connections.
+///
+/// The frames it is put into are kept, so every connection with the same compression
+/// settings shares one and sending to it only costs a reference count. Clones share them too.
+#[derive(Clone)]
+pub struct EncodedPacket(Arc<EncodedFrames>);
+
+/// Whether a connection is compressing, and its threshold.
+type FrameSettings = (bool, Option<usize>);
+
+struct EncodedFrames {
+    data: PacketData,
+    // rarely more than one
+    frames: Mutex<Vec<(FrameSettings, Bytes)>>,
+}
+
+impl EncodedPacket {
+    pub fn new<T: Encode + Packet>(packet: &T) -> Result<Self> {
+        Ok(Self(Arc::new(EncodedFrames {
+            data: PacketData::from_packet(packet)?,
+            frames: Mutex::new(Vec::new()),
+        })))
+    }
+
+    pub fn packet_id(&self) -> VarInt {
+        self.0.data.packet_id
+    }
+
+    /// The packet framed for a connection with these settings, compressed the first time
+    /// it is asked for.
+    pub fn frame(&self, compressing: bool, compression_threshold: Option<usize>) -> Result<Bytes> {
+        let settings = (compressing, compression_threshold);
+        // held while compressing, so that only happens once
+        let mut frames = self.0.frames.lock().unwrap();
+
+        if let Some((_, frame)) = frames.iter().find(|(key, _)| *key == settings) {
+            return Ok(frame.clone());
+        }
+
+        let frame =
+            SerializedPacket::new(self.0.data.clone(), compressing).frame(compression_threshold)?;
+        frames.push((settings, frame.clone()));
+        Ok(frame)
+    }
+}
+
+/// Writes what [`PacketSender`]s queue to the client, as a task of its own.
+pub struct PacketWriter {
+    tcp: BufWriter<CipherStream<TcpStream>>,
+    queue: Receiver<Bytes>,
+    flush_interval: Duration,
+}
+
+impl PacketWriter {
+    /// Writes frames until every sender is dropped or closed, or the connection fails, and
+    /// closes the connection after.
+    ///
+    /// Frames are flushed [`SendQueueLimits::flush_interval`] after the first of them is
+    /// written, so a burst of packets sent in one tick costs a single flush.
+    pub async fn run(mut self) -> Result<()> {
+        let result = self.write_queued().await;
+
+        // also stops the reader, so whoever runs it notices the player is gone
+        let _ = self.tcp.get_ref().get_ref().shutdown(Shutdown::Both);
+        result
+    }
+
+    async fn write_queued(&mut self) -> Result<()> {
+        let mut flush_at = None;
+
+        loop {
+            let frame = match flush_at {
+                // checked first, a queue that never empties mustn't hold back the flush
+                Some(at) if Instant::now() >= at => {
+                    self.tcp.flush().await?;
+                    flush_at = None;
+                    continue;
+                }
+                Some(at) => {
+                    let wait = at.saturating_duration_since(Instant::now());
+                    match async_std::future::timeout(wait, self.queue.recv()).await {
+                        Ok(frame) => frame,
+                        Err(_) => continue,
+                    }
+                }
+                None => self.queue.recv().await,
+            };
+            let Ok(frame) = frame else { break };
+
+            self.tcp.write_all(&frame).await?;
+            flush_at.get_or_insert_with(|| Instant::now() + self.flush_interval);
+        }
+
+        // what was queued before the close still goes out
+        Ok(self.tcp.flush().await?)
+    }
+}
@@ -13,16 +13,18 @@ impl Packet for Handshake {
     const STATE: State = State::Handshaking;
 }
 
-impl AsyncDeserializeContexful for Handshake {
-    type Context = PacketContext;
-
-    async fn read_with_context(reader: &mut impl ReadExt, context: &Self::Context) -> Result<Self> {
+impl Handshake {
+    async fn read_with_address_limit(
+        reader: &mut impl ReadExt,
+        context: &PacketContext,
+        address_limit: usize,
+    ) -> Result<Self> {
         try {
             Self::check_context(context)?;
 
             Self {
                 protocol_version: reader.deserialize().await?,
-                address: read_string_limit(reader, 256).await?,
+                address: read_string_limit(reader, address_limit).await?,
                 port: reader.deserialize().await?,
                 next_state: read_enum! { [reader.deserialize::<VarInt>().await?.0]
                     1 => State::Status,
@@ -31,6 +33,83 @@ impl AsyncDeserializeContexful for Handshake {
             }
         }
     }
+
+    fn decode_with_address_limit(
+        buf: &mut impl Buf,
+        context: &PacketContext,
+        address_limit: usize,
+    ) -> Result<Self> {
+        Self::check_context(context)?;
+
+        Ok(Self {
+            protocol_version: VarInt::decode(buf)?,
+            address: decode_string_limit(buf, address_limit)?,
+            port: u16::decode(buf)?,
+            next_state: read_enum! { [VarInt::<i32>::decode(buf)?.0]
+                1 => State::Status,
+                2 => State::Play
+            },
+        })
+    }
+
+    fn next_state_id(&self) -> Result<VarInt> {
+        match self.next_state {
+            State::Status => Ok(VarInt(1)),
+            State::Play => Ok(VarInt(2)),
+            other => Err(Error::BadEnumValue {
+                values: vec![String::from("State::Status"), String::from("State::Play")],
+                got: format!("{other:?}"),
+            }),
+        }
+    }
+}
+
+impl AsyncDeserializeContexful for Handshake {
+    type Context = PacketContext;
+
+    async fn read_with_context(reader: &mut impl ReadExt, context: &Self::Context) -> Result<Self> {
+        Self::read_with_address_limit(reader, context, 255).await
+    }
+}
+
+/// A handshake from behind a BungeeCord proxy with IP forwarding enabled, which appends
+/// the player's IP, UUID and properties to the address and so exceeds its usual limit.
+#[derive(Debug)]
+pub struct ForwardedHandshake(pub Handshake);
+
+impl Packet for ForwardedHandshake {
+    const ID: VarInt<i32> = Handshake::ID;
+    const STATE: State = Handshake::STATE;
+}
+
+impl AsyncDeserializeContexful for ForwardedHandshake {
+    type Context = PacketContext;
+
+    async fn read_with_context(reader: &mut impl ReadExt, context: &Self::Context) -> Result<Self> {
+        Handshake::read_with_address_limit(reader, context, MAX_STRING_LENGTH)
+            .await
+            .map(Self)
+    }
+}
+
+impl AsyncSerialize for ForwardedHandshake {
+    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+        self.0.write_to(writer).await
+    }
+}
+
+impl DecodeContextful for ForwardedHandshake {
+    type Context = PacketContext;
+
+    fn decode_with_context(buf: &mut impl Buf, context: &Self::Context) -> Result<Self> {
+        Handshake::decode_with_address_limit(buf, context, MAX_STRING_LENGTH).map(Self)
+    }
+}
+
+impl Encode for ForwardedHandshake {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.0.encode(buf)
+    }
 }
 
 impl AsyncSerialize for Handshake {
@@ -39,21 +118,30 @@ impl AsyncSerialize for Handshake {
             writer.serialize(&self.protocol_version).await?;
             writer.serialize(&self.address).await?;
             writer.serialize(&self.port).await?;
-            writer
-                .serialize(&match self.next_state {
-                    State::Status => 1,
-                    State::Play => 2,
-                    other => {
-                        return Err(Error::BadEnumValue {
-                            values: vec![
-                                String::from("State::Status"),
-                                String::from("State::Play"),
-                            ],
-                            got: format!("{other:?}"),
-                        })
-                    }
-                })
-                .await?;
+            writer.serialize(&self.next_state_id()?).await?;
         }
     }
 }
+
+impl DecodeContextful for Handshake {
+    type Context = PacketContext;
+
+    fn decode_with_context(buf: &mut impl Buf, context: &Self::Context) -> Result<Self> {
+        Self::decode_with_address_limit(buf, context, 255)
+    }
+}
+
+impl Encode for Handshake {
+    fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+        self.protocol_version.encode(buf)?;
+        self.address.encode(buf)?;
+        self.port.encode(buf)?;
+        self.next_state_id()?.encode(buf)
+    }
+}
+
+packet_enum! {
+    pub enum ServerboundHandshaking in Handshaking {
+        Handshake(Handshake),
+    }
+}
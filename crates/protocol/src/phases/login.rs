@@ -1,88 +1,118 @@
-use crate::model::Gamemode;
+use serde::{Deserialize, Serialize};
+
+use crate::model::TextComponent;
 
 use super::*;
 
-#[derive(Debug)]
+#[derive(Debug, Packet)]
+#[packet(id = 0x00, state = Login)]
 pub struct LoginStart {
+    #[limit(16)]
     pub username: String,
     pub uuid: Option<Uuid>,
 }
 
-impl AsyncSerialize for LoginStart {
-    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
-        try {
-            writer.serialize(&self.username).await?;
-            writer.serialize(&self.uuid).await?;
-        }
-    }
+/// Closes the connection during login, showing the player `reason`.
+#[derive(Debug, Packet)]
+#[packet(id = 0x00, state = Login)]
+pub struct Disconnect {
+    #[json]
+    #[limit(MAX_CHAT_LENGTH)]
+    pub reason: TextComponent,
 }
 
-impl AsyncDeserializeContexful for LoginStart {
-    type Context = PacketContext;
-
-    async fn read_with_context(reader: &mut impl ReadExt, context: &Self::Context) -> Result<Self> {
-        try {
-            Self::check_context(context)?;
-
-            Self {
-                username: read_string_limit(reader, 16).await?,
-                uuid: reader.deserialize().await?,
-            }
-        }
-    }
+#[derive(Debug, Packet)]
+#[packet(id = 0x01, state = Login)]
+pub struct EncryptionRequest {
+    /// Always empty since 1.7.
+    pub server_id: StringLimit<20>,
+    pub public_key: Vec<u8>,
+    pub verify_token: Vec<u8>,
 }
 
-impl Packet for LoginStart {
-    const STATE: State = State::Login;
-    const ID: VarInt<i32> = VarInt(0x00);
+#[derive(Debug, Packet)]
+#[packet(id = 0x01, state = Login)]
+pub struct EncryptionResponse {
+    /// Encrypted with the server's public key.
+    pub shared_secret: Vec<u8>,
+    /// Encrypted with the server's public key.
+    pub verify_token: Vec<u8>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Packet)]
+#[packet(id = 0x02, state = Login)]
 pub struct LoginSuccess {
     pub uuid: Uuid,
+    #[limit(16)]
     pub username: String,
+    pub properties: Vec<Property>,
 }
 
-#[derive(Debug)]
+/// A profile property such as `textures`, as handed out by the session server.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    AsyncSerialize,
+    AsyncDeserialize,
+    Encode,
+    Decode,
+)]
 pub struct Property {
+    #[limit(MAX_STRING_LENGTH)]
     pub name: String,
+    #[limit(MAX_STRING_LENGTH)]
     pub value: String,
-    pub signature: Option<StringLimit<16>>
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[limit(MAX_STRING_LENGTH)]
+    pub signature: Option<String>,
 }
 
-impl AsyncSerialize for LoginSuccess {
-    async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
-        try {
-            writer.serialize(&self.uuid).await?;
-            writer.serialize(&self.username).await?;
-            writer.serialize(&VarInt(0)).await?;
-        }
-    }
+#[derive(Debug, Packet)]
+#[packet(id = 0x03, state = Login)]
+pub struct SetCompression {
+    /// Packets of at least this many bytes are compressed, negative values disable compression.
+    pub threshold: VarInt,
 }
 
-impl AsyncDeserializeContexful for LoginSuccess {
-    type Context = PacketContext;
-
-    async fn read_with_context(reader: &mut impl ReadExt, context: &Self::Context) -> Result<Self> {
-        try {
-            Self::check_context(context)?;
+#[derive(Debug, Packet)]
+#[packet(id = 0x04, state = Login)]
+pub struct LoginPluginRequest {
+    /// Chosen by the server, the client's response carries the same id.
+    pub message_id: VarInt,
+    #[limit(MAX_STRING_LENGTH)]
+    pub channel: String,
+    /// Everything up to the end of the packet.
+    #[rest]
+    pub data: Bytes,
+}
 
-            Self {
-                uuid: reader.deserialize().await?,
-                username: read_string_limit(reader, 16).await?,
-                // we don't care about properties
-            }
-        }
-    }
+#[derive(Debug, Packet)]
+#[packet(id = 0x02, state = Login)]
+pub struct LoginPluginResponse {
+    pub message_id: VarInt,
+    /// `None` if the client did not understand the request.
+    #[rest]
+    pub data: Option<Bytes>,
 }
 
-impl Packet for LoginSuccess {
-    const ID: VarInt<i32> = VarInt(0x02);
-    const STATE: State = State::Login;
+packet_enum! {
+    pub enum ServerboundLogin in Login {
+        LoginStart(LoginStart),
+        EncryptionResponse(EncryptionResponse),
+        LoginPluginResponse(LoginPluginResponse),
+    }
 }
 
-pub struct LoginPlay {
-    pub entity_id: i32,
-    pub is_hardcore: bool,
-    pub game_mode: Gamemode
+packet_enum! {
+    pub enum ClientboundLogin in Login {
+        Disconnect(Disconnect),
+        EncryptionRequest(EncryptionRequest),
+        LoginSuccess(LoginSuccess),
+        SetCompression(SetCompression),
+        LoginPluginRequest(LoginPluginRequest),
+    }
 }
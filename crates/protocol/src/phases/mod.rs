@@ -1,5 +1,88 @@
 use super::*;
 
+/// Declares an enum of every packet sent in one direction during one state, which reads
+/// whichever of them the packet id says.
+macro_rules! packet_enum {
+    ($(#[$meta:meta])* $vis:vis enum $name:ident in $state:ident {
+        $($variant:ident($packet:ty)),* $(,)?
+    }) => {
+        $(#[$meta])*
+        // packets are matched on right after being read, boxing the large ones isn't worth it
+        #[allow(clippy::large_enum_variant)]
+        #[derive(Debug)]
+        $vis enum $name {
+            $($variant($packet),)*
+        }
+
+        impl $name {
+            pub fn id(&self) -> VarInt {
+                match self {
+                    $(Self::$variant(_) => <$packet as Packet>::ID,)*
+                }
+            }
+        }
+
+        impl AsyncDeserializeContexful for $name {
+            type Context = PacketContext;
+
+            async fn read_with_context(
+                reader: &mut impl ReadExt,
+                context: &Self::Context,
+            ) -> Result<Self> {
+                match context.id {
+                    $(id if id == <$packet as Packet>::ID && context.state == State::$state => {
+                        reader.deserialize_with_context(context).await.map(Self::$variant)
+                    })*
+                    id => Err(Error::UnknownPacket {
+                        id,
+                        state: context.state,
+                    }),
+                }
+            }
+        }
+
+        impl DecodeContextful for $name {
+            type Context = PacketContext;
+
+            fn decode_with_context(buf: &mut impl Buf, context: &Self::Context) -> Result<Self> {
+                match context.id {
+                    $(id if id == <$packet as Packet>::ID && context.state == State::$state => {
+                        <$packet>::decode_with_context(buf, context).map(Self::$variant)
+                    })*
+                    id => Err(Error::UnknownPacket {
+                        id,
+                        state: context.state,
+                    }),
+                }
+            }
+        }
+
+        impl AsyncSerialize for $name {
+            async fn write_to(&self, writer: &mut impl WriteExt) -> Result<()> {
+                match self {
+                    $(Self::$variant(packet) => packet.write_to(writer).await,)*
+                }
+            }
+        }
+
+        impl Encode for $name {
+            fn encode(&self, buf: &mut impl BufMut) -> Result<()> {
+                match self {
+                    $(Self::$variant(packet) => packet.encode(buf),)*
+                }
+            }
+        }
+
+        $(
+        impl From<$packet> for $name {
+            fn from(packet: $packet) -> Self {
+                Self::$variant(packet)
+            }
+        }
+        )*
+    };
+}
+
 pub mod handshaking;
 
 pub mod login;
@@ -0,0 +1,190 @@
+use crate::{
+    model::{
+        Ability, ChatMode, Difficulty, Gamemode, MainHand, OptionalGamemode, RelativeMovement,
+        TextComponent,
+    },
+    nbt::Nbt,
+};
+
+use super::*;
+
+/// Sent by the client once it has moved to where Synchronize Player Position put it.
+#[derive(Debug, Packet)]
+#[packet(id = 0x00, state = Play)]
+pub struct ConfirmTeleportation {
+    #[varint]
+    pub teleport_id: i32,
+}
+
+/// The client's settings, sent when joining and whenever they change.
+#[derive(Debug, Clone, Packet)]
+#[packet(id = 0x08, state = Play)]
+pub struct ClientInformation {
+    /// Such as `en_us`.
+    #[limit(16)]
+    pub locale: String,
+    pub view_distance: i8,
+    pub chat_mode: ChatMode,
+    pub chat_colors: bool,
+    /// A bit mask of cape, jacket, left and right sleeve, left and right pants leg and hat.
+    pub displayed_skin_parts: u8,
+    pub main_hand: MainHand,
+    pub enable_text_filtering: bool,
+    pub allow_server_listings: bool,
+}
+
+#[derive(Debug, Packet)]
+#[packet(id = 0x0D, state = Play)]
+pub struct ServerboundPluginMessage {
+    #[limit(MAX_STRING_LENGTH)]
+    pub channel: String,
+    #[rest]
+    pub data: Bytes,
+}
+
+/// The client's answer to a [`ClientboundKeepAlive`], with the same id.
+#[derive(Debug, Packet)]
+#[packet(id = 0x12, state = Play)]
+pub struct ServerboundKeepAlive {
+    pub keep_alive_id: i64,
+}
+
+#[derive(Debug, Packet)]
+#[packet(id = 0x0C, state = Play)]
+pub struct ChangeDifficulty {
+    pub difficulty: Difficulty,
+    pub locked: bool,
+}
+
+#[derive(Debug, Packet)]
+#[packet(id = 0x17, state = Play)]
+pub struct ClientboundPluginMessage {
+    #[limit(MAX_STRING_LENGTH)]
+    pub channel: String,
+    #[rest]
+    pub data: Bytes,
+}
+
+/// Closes the connection during play, showing the player `reason`.
+#[derive(Debug, Packet)]
+#[packet(id = 0x1A, state = Play)]
+pub struct Disconnect {
+    #[json]
+    #[limit(MAX_CHAT_LENGTH)]
+    pub reason: TextComponent,
+}
+
+/// Sent every so often, the client disconnects if it hears nothing for too long and the
+/// server if the id doesn't come back.
+#[derive(Debug, Packet)]
+#[packet(id = 0x23, state = Play)]
+pub struct ClientboundKeepAlive {
+    pub keep_alive_id: i64,
+}
+
+/// The first packet of play, with what the client needs to know about the world it joins.
+#[derive(Debug, Packet)]
+#[packet(id = 0x28, state = Play)]
+pub struct LoginPlay {
+    pub entity_id: i32,
+    pub is_hardcore: bool,
+    pub game_mode: Gamemode,
+    pub previous_game_mode: OptionalGamemode,
+    /// Every dimension on the server.
+    pub dimension_names: Vec<Identifier>,
+    /// The registries, such as dimension types and biomes, the client has to agree on.
+    pub registry_codec: Nbt,
+    pub dimension_type: Identifier,
+    /// The dimension the player spawns in.
+    pub dimension_name: Identifier,
+    /// The first 8 bytes of the SHA-256 hash of the world seed, for biome noise.
+    pub hashed_seed: i64,
+    /// Ignored by the client.
+    #[varint]
+    pub max_players: i32,
+    #[varint]
+    pub view_distance: i32,
+    #[varint]
+    pub simulation_distance: i32,
+    pub reduced_debug_info: bool,
+    pub enable_respawn_screen: bool,
+    /// Whether this is a debug world, with every block state laid out.
+    pub is_debug: bool,
+    /// Whether this is a superflat world, which has its horizon lower.
+    pub is_flat: bool,
+    pub death_location: Option<DeathLocation>,
+    #[varint]
+    pub portal_cooldown: i32,
+}
+
+/// Where the player last died, for recovery compasses.
+#[derive(Debug, Clone, PartialEq, Eq, AsyncSerialize, AsyncDeserialize, Encode, Decode)]
+pub struct DeathLocation {
+    pub dimension_name: Identifier,
+    pub location: Position,
+}
+
+#[derive(Debug, Packet)]
+#[packet(id = 0x34, state = Play)]
+pub struct PlayerAbilities {
+    pub flags: EnumSet<Ability>,
+    /// `0.05` by default.
+    pub flying_speed: f32,
+    /// Scales the field of view, `0.1` by default.
+    pub field_of_view_modifier: f32,
+}
+
+/// Moves the player, who has to confirm it with [`ConfirmTeleportation`] before the
+/// server accepts its movement again.
+#[derive(Debug, Packet)]
+#[packet(id = 0x3C, state = Play)]
+pub struct SynchronizePlayerPosition {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// The fields that are offsets from where the player is rather than absolute.
+    pub flags: EnumSet<RelativeMovement>,
+    #[varint]
+    pub teleport_id: i32,
+}
+
+/// Selects a hotbar slot.
+#[derive(Debug, Packet)]
+#[packet(id = 0x4D, state = Play)]
+pub struct SetHeldItem {
+    /// From 0 to 8.
+    pub slot: i8,
+}
+
+/// Where compasses point, and where the client spawns while the world loads.
+#[derive(Debug, Packet)]
+#[packet(id = 0x50, state = Play)]
+pub struct SetDefaultSpawnPosition {
+    pub location: Position,
+    pub angle: f32,
+}
+
+packet_enum! {
+    pub enum ServerboundPlay in Play {
+        ConfirmTeleportation(ConfirmTeleportation),
+        ClientInformation(ClientInformation),
+        PluginMessage(ServerboundPluginMessage),
+        KeepAlive(ServerboundKeepAlive),
+    }
+}
+
+packet_enum! {
+    pub enum ClientboundPlay in Play {
+        ChangeDifficulty(ChangeDifficulty),
+        PluginMessage(ClientboundPluginMessage),
+        Disconnect(Disconnect),
+        KeepAlive(ClientboundKeepAlive),
+        LoginPlay(LoginPlay),
+        PlayerAbilities(PlayerAbilities),
+        SynchronizePlayerPosition(SynchronizePlayerPosition),
+        SetHeldItem(SetHeldItem),
+        SetDefaultSpawnPosition(SetDefaultSpawnPosition),
+    }
+}
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::TextComponent;
+
+use super::*;
+
+#[derive(Debug, Packet)]
+#[packet(id = 0x00, state = Status)]
+pub struct StatusRequest;
+
+/// The JSON document shown in the multiplayer server list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    pub version: StatusVersion,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub players: Option<StatusPlayers>,
+    /// A chat component, usually the MOTD.
+    #[serde(default)]
+    pub description: TextComponent,
+    /// A `data:image/png;base64,` URI of a 64x64 PNG image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+    #[serde(default)]
+    pub enforces_secure_chat: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusVersion {
+    pub name: String,
+    pub protocol: i32,
+}
+
+impl Default for StatusVersion {
+    fn default() -> Self {
+        Self {
+            name: String::from(VERSION_NAME),
+            protocol: PROTOCOL_VERSION.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusPlayers {
+    pub max: i32,
+    pub online: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sample: Vec<StatusPlayer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusPlayer {
+    pub name: String,
+    pub id: Uuid,
+}
+
+#[derive(Debug, Packet)]
+#[packet(id = 0x00, state = Status)]
+pub struct StatusResponse {
+    #[json]
+    pub status: ServerStatus,
+}
+
+#[derive(Debug, Packet)]
+#[packet(id = 0x01, state = Status)]
+pub struct PingRequest {
+    pub payload: i64,
+}
+
+#[derive(Debug, Packet)]
+#[packet(id = 0x01, state = Status)]
+pub struct PongResponse {
+    pub payload: i64,
+}
+
+packet_enum! {
+    pub enum ServerboundStatus in Status {
+        StatusRequest(StatusRequest),
+        PingRequest(PingRequest),
+    }
+}
+
+packet_enum! {
+    pub enum ClientboundStatus in Status {
+        StatusResponse(StatusResponse),
+        PongResponse(PongResponse),
+    }
+}
@@ -0,0 +1,154 @@
+use super::*;
+
+/// The climate and looks of a biome, as far as the client renders them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Biome {
+    /// Rain or snow, depending on the temperature.
+    pub has_precipitation: bool,
+    pub temperature: f32,
+    pub temperature_modifier: Option<TemperatureModifier>,
+    /// Affects grass and foliage colors.
+    pub downfall: f32,
+    pub effects: BiomeEffects,
+}
+
+impl Biome {
+    /// The biome the client falls back to, so it has to be in every registry.
+    pub fn plains() -> Self {
+        Self {
+            has_precipitation: true,
+            temperature: 0.8,
+            temperature_modifier: None,
+            downfall: 0.4,
+            effects: BiomeEffects {
+                sky_color: 0x78a7ff,
+                ..BiomeEffects::default()
+            },
+        }
+    }
+
+    pub fn the_void() -> Self {
+        Self {
+            has_precipitation: false,
+            temperature: 0.5,
+            temperature_modifier: None,
+            downfall: 0.5,
+            effects: BiomeEffects {
+                sky_color: 0x7ba4ff,
+                ..BiomeEffects::default()
+            },
+        }
+    }
+
+    pub fn vanilla() -> Registry<Self> {
+        Registry::new(minecraft("worldgen/biome"))
+            .with(minecraft("plains"), Self::plains())
+            .with(minecraft("the_void"), Self::the_void())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemperatureModifier {
+    None,
+    /// Patches of the biome are cold enough to snow, as in frozen oceans.
+    Frozen,
+}
+
+/// Colors, particles, sounds and music, with colors as `0xrrggbb`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BiomeEffects {
+    pub fog_color: i32,
+    pub water_color: i32,
+    pub water_fog_color: i32,
+    pub sky_color: i32,
+    /// Computed from the temperature and downfall when `None`.
+    pub foliage_color: Option<i32>,
+    /// Computed from the temperature and downfall when `None`.
+    pub grass_color: Option<i32>,
+    pub grass_color_modifier: Option<GrassColorModifier>,
+    pub particle: Option<BiomeParticle>,
+    /// Played continuously, such as `minecraft:ambient.basalt_deltas.loop`.
+    pub ambient_sound: Option<Identifier>,
+    pub mood_sound: Option<MoodSound>,
+    pub additions_sound: Option<AdditionsSound>,
+    pub music: Option<BiomeMusic>,
+}
+
+impl Default for BiomeEffects {
+    /// The colors and cave sounds most overworld biomes share.
+    fn default() -> Self {
+        Self {
+            fog_color: 0xc0d8ff,
+            water_color: 0x3f76e4,
+            water_fog_color: 0x050533,
+            sky_color: 0x78a7ff,
+            foliage_color: None,
+            grass_color: None,
+            grass_color_modifier: None,
+            particle: None,
+            ambient_sound: None,
+            mood_sound: Some(MoodSound::cave()),
+            additions_sound: None,
+            music: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrassColorModifier {
+    None,
+    DarkForest,
+    Swamp,
+}
+
+/// Particles floating around, such as the ash in basalt deltas.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BiomeParticle {
+    /// The particle's `type` and whatever options it takes.
+    pub options: Compound,
+    /// The chance of one spawning per block each tick.
+    pub probability: f32,
+}
+
+/// A sound played every so often while the player is in the dark.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MoodSound {
+    pub sound: Identifier,
+    pub tick_delay: i32,
+    /// How far around the player to look for darkness.
+    pub block_search_extent: i32,
+    /// How far from the player it is played.
+    pub offset: f64,
+}
+
+impl MoodSound {
+    /// The cave ambience of the overworld.
+    pub fn cave() -> Self {
+        Self {
+            sound: minecraft("ambient.cave"),
+            tick_delay: 6000,
+            block_search_extent: 8,
+            offset: 2.0,
+        }
+    }
+}
+
+/// A sound played at random on top of the ambient sound.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AdditionsSound {
+    pub sound: Identifier,
+    /// The chance of it playing each tick.
+    pub tick_chance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BiomeMusic {
+    pub sound: Identifier,
+    /// In ticks.
+    pub min_delay: i32,
+    /// In ticks.
+    pub max_delay: i32,
+    pub replace_current_music: bool,
+}
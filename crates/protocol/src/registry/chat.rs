@@ -0,0 +1,111 @@
+use crate::model::{Color, Style};
+
+use super::*;
+
+/// How a kind of chat message is shown and narrated.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChatType {
+    pub chat: ChatDecoration,
+    pub narration: ChatDecoration,
+}
+
+impl ChatType {
+    /// Narrated as a plain chat message.
+    fn narrated_as_chat(chat: ChatDecoration) -> Self {
+        Self {
+            chat,
+            narration: ChatDecoration::new(
+                "chat.type.text.narrate",
+                [ChatParameter::Sender, ChatParameter::Content],
+            ),
+        }
+    }
+
+    pub fn vanilla() -> Registry<Self> {
+        use ChatParameter::*;
+
+        let whisper = Style {
+            color: Some(Color::Gray),
+            italic: Some(true),
+            ..Style::default()
+        };
+
+        Registry::new(minecraft("chat_type"))
+            .with(
+                minecraft("chat"),
+                Self::narrated_as_chat(ChatDecoration::new("chat.type.text", [Sender, Content])),
+            )
+            .with(
+                minecraft("emote_command"),
+                Self {
+                    chat: ChatDecoration::new("chat.type.emote", [Sender, Content]),
+                    narration: ChatDecoration::new("chat.type.emote", [Sender, Content]),
+                },
+            )
+            .with(
+                minecraft("msg_command_incoming"),
+                Self::narrated_as_chat(ChatDecoration {
+                    style: Some(whisper.clone()),
+                    ..ChatDecoration::new("commands.message.display.incoming", [Sender, Content])
+                }),
+            )
+            .with(
+                minecraft("msg_command_outgoing"),
+                Self::narrated_as_chat(ChatDecoration {
+                    style: Some(whisper),
+                    ..ChatDecoration::new("commands.message.display.outgoing", [Target, Content])
+                }),
+            )
+            .with(
+                minecraft("say_command"),
+                Self::narrated_as_chat(ChatDecoration::new(
+                    "chat.type.announcement",
+                    [Sender, Content],
+                )),
+            )
+            .with(
+                minecraft("team_msg_command_incoming"),
+                Self::narrated_as_chat(ChatDecoration::new(
+                    "chat.type.team.text",
+                    [Target, Sender, Content],
+                )),
+            )
+            .with(
+                minecraft("team_msg_command_outgoing"),
+                Self::narrated_as_chat(ChatDecoration::new(
+                    "chat.type.team.sent",
+                    [Target, Sender, Content],
+                )),
+            )
+    }
+}
+
+/// A translation the parts of a message are substituted into, in order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChatDecoration {
+    pub translation_key: String,
+    pub parameters: Vec<ChatParameter>,
+    pub style: Option<Style>,
+}
+
+impl ChatDecoration {
+    pub fn new(
+        translation_key: impl Into<String>,
+        parameters: impl IntoIterator<Item = ChatParameter>,
+    ) -> Self {
+        Self {
+            translation_key: translation_key.into(),
+            parameters: parameters.into_iter().collect(),
+            style: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatParameter {
+    Sender,
+    /// Who a private or team message is sent to.
+    Target,
+    Content,
+}
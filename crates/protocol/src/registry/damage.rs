@@ -0,0 +1,149 @@
+use super::*;
+
+/// A source of damage, and how it is shown when it kills.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DamageType {
+    /// Part of the death message's translation key, as in `death.attack.<message_id>`.
+    pub message_id: String,
+    pub scaling: DamageScaling,
+    /// Hunger caused by taking the damage.
+    pub exhaustion: f32,
+    /// The hurt sound, [`DamageEffects::Hurt`] when `None`.
+    pub effects: Option<DamageEffects>,
+    /// [`DeathMessageType::Default`] when `None`.
+    pub death_message_type: Option<DeathMessageType>,
+}
+
+impl DamageType {
+    fn new(message_id: &str, exhaustion: f32) -> Self {
+        Self {
+            message_id: String::from(message_id),
+            scaling: DamageScaling::WhenCausedByLivingNonPlayer,
+            exhaustion,
+            effects: None,
+            death_message_type: None,
+        }
+    }
+
+    fn scaling(self, scaling: DamageScaling) -> Self {
+        Self { scaling, ..self }
+    }
+
+    fn effects(self, effects: DamageEffects) -> Self {
+        Self {
+            effects: Some(effects),
+            ..self
+        }
+    }
+
+    /// Every damage type of 1.20.1, all of which the client expects to exist.
+    pub fn vanilla() -> Registry<Self> {
+        use DamageEffects::*;
+        use DamageScaling::Always;
+
+        let damage_types = [
+            ("arrow", Self::new("arrow", 0.1)),
+            (
+                "bad_respawn_point",
+                Self {
+                    death_message_type: Some(DeathMessageType::IntentionalGameDesign),
+                    ..Self::new("badRespawnPoint", 0.1).scaling(Always)
+                },
+            ),
+            ("cactus", Self::new("cactus", 0.1)),
+            ("cramming", Self::new("cramming", 0.0)),
+            ("dragon_breath", Self::new("dragonBreath", 0.0)),
+            ("drown", Self::new("drown", 0.0).effects(Drowning)),
+            ("dry_out", Self::new("dryout", 0.1)),
+            ("explosion", Self::new("explosion", 0.1).scaling(Always)),
+            (
+                "fall",
+                Self {
+                    death_message_type: Some(DeathMessageType::FallVariants),
+                    ..Self::new("fall", 0.0)
+                },
+            ),
+            ("falling_anvil", Self::new("anvil", 0.1)),
+            ("falling_block", Self::new("fallingBlock", 0.1)),
+            ("falling_stalactite", Self::new("fallingStalactite", 0.1)),
+            ("fireball", Self::new("fireball", 0.1).effects(Burning)),
+            ("fireworks", Self::new("fireworks", 0.1)),
+            ("fly_into_wall", Self::new("flyIntoWall", 0.0)),
+            ("freeze", Self::new("freeze", 0.0).effects(Freezing)),
+            ("generic", Self::new("generic", 0.0)),
+            ("generic_kill", Self::new("genericKill", 0.0)),
+            ("hot_floor", Self::new("hotFloor", 0.1).effects(Burning)),
+            ("in_fire", Self::new("inFire", 0.1).effects(Burning)),
+            ("in_wall", Self::new("inWall", 0.0)),
+            ("indirect_magic", Self::new("indirectMagic", 0.0)),
+            ("lava", Self::new("lava", 0.1).effects(Burning)),
+            ("lightning_bolt", Self::new("lightningBolt", 0.1)),
+            ("magic", Self::new("magic", 0.0)),
+            ("mob_attack", Self::new("mob", 0.1)),
+            ("mob_attack_no_aggro", Self::new("mob", 0.1)),
+            ("mob_projectile", Self::new("mob", 0.1)),
+            ("on_fire", Self::new("onFire", 0.0).effects(Burning)),
+            ("out_of_world", Self::new("outOfWorld", 0.0)),
+            ("outside_border", Self::new("outsideBorder", 0.0)),
+            ("player_attack", Self::new("player", 0.1)),
+            (
+                "player_explosion",
+                Self::new("explosion.player", 0.1).scaling(Always),
+            ),
+            ("sonic_boom", Self::new("sonic_boom", 0.0).scaling(Always)),
+            ("stalagmite", Self::new("stalagmite", 0.0)),
+            ("starve", Self::new("starve", 0.0)),
+            ("sting", Self::new("sting", 0.1)),
+            (
+                "sweet_berry_bush",
+                Self::new("sweetBerryBush", 0.1).effects(Poking),
+            ),
+            ("thorns", Self::new("thorns", 0.1).effects(Thorns)),
+            ("thrown", Self::new("thrown", 0.1)),
+            ("trident", Self::new("trident", 0.1)),
+            (
+                "unattributed_fireball",
+                Self::new("onFire", 0.1).effects(Burning),
+            ),
+            ("wither", Self::new("wither", 0.0)),
+            ("wither_skull", Self::new("witherSkull", 0.1)),
+        ];
+
+        let mut registry = Registry::new(minecraft("damage_type"));
+        for (name, damage_type) in damage_types {
+            registry.insert(minecraft(name), damage_type);
+        }
+        registry
+    }
+}
+
+/// When the damage grows with the world's difficulty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageScaling {
+    Never,
+    WhenCausedByLivingNonPlayer,
+    Always,
+}
+
+/// The sound played when the damage is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageEffects {
+    Hurt,
+    Thorns,
+    Drowning,
+    Burning,
+    Poking,
+    Freezing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathMessageType {
+    Default,
+    /// Says what the player fell from, such as a ladder or vines.
+    FallVariants,
+    /// Links to the bug report about beds exploding outside the overworld.
+    IntentionalGameDesign,
+}
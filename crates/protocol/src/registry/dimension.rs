@@ -0,0 +1,169 @@
+use super::*;
+
+/// How a dimension behaves: its height, lighting, sky and which blocks work in it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DimensionType {
+    /// The time of day that is always shown, `None` for a regular day cycle.
+    pub fixed_time: Option<i64>,
+    pub has_skylight: bool,
+    pub has_ceiling: bool,
+    /// Water evaporates, and lava spreads further.
+    pub ultrawarm: bool,
+    /// Compasses and clocks work, and sleeping is possible.
+    pub natural: bool,
+    /// How far each block here takes you in the overworld, `8.0` in the nether.
+    pub coordinate_scale: f64,
+    pub bed_works: bool,
+    pub respawn_anchor_works: bool,
+    /// A multiple of 16.
+    pub min_y: i32,
+    /// A multiple of 16, from `min_y` up.
+    pub height: i32,
+    /// How high portals and chorus fruit may take you.
+    pub logical_height: i32,
+    /// A block tag such as `#minecraft:infiniburn_overworld`.
+    pub infiniburn: String,
+    /// Which sky, fog and clouds are rendered, as in `minecraft:the_end`.
+    pub effects: Identifier,
+    pub ambient_light: f32,
+    pub piglin_safe: bool,
+    pub has_raids: bool,
+    pub monster_spawn_light_level: MonsterSpawnLightLevel,
+    pub monster_spawn_block_light_limit: i32,
+}
+
+impl DimensionType {
+    pub fn overworld() -> Self {
+        Self {
+            fixed_time: None,
+            has_skylight: true,
+            has_ceiling: false,
+            ultrawarm: false,
+            natural: true,
+            coordinate_scale: 1.0,
+            bed_works: true,
+            respawn_anchor_works: false,
+            min_y: -64,
+            height: 384,
+            logical_height: 384,
+            infiniburn: String::from("#minecraft:infiniburn_overworld"),
+            effects: minecraft("overworld"),
+            ambient_light: 0.0,
+            piglin_safe: false,
+            has_raids: true,
+            monster_spawn_light_level: MonsterSpawnLightLevel::Uniform {
+                min_inclusive: 0,
+                max_inclusive: 7,
+            },
+            monster_spawn_block_light_limit: 0,
+        }
+    }
+
+    /// The overworld with a ceiling, as used by the single biome caves world preset.
+    pub fn overworld_caves() -> Self {
+        Self {
+            has_ceiling: true,
+            ..Self::overworld()
+        }
+    }
+
+    pub fn the_nether() -> Self {
+        Self {
+            fixed_time: Some(18000),
+            has_skylight: false,
+            has_ceiling: true,
+            ultrawarm: true,
+            natural: false,
+            coordinate_scale: 8.0,
+            bed_works: false,
+            respawn_anchor_works: true,
+            min_y: 0,
+            height: 256,
+            logical_height: 128,
+            infiniburn: String::from("#minecraft:infiniburn_nether"),
+            effects: minecraft("the_nether"),
+            ambient_light: 0.1,
+            piglin_safe: true,
+            has_raids: false,
+            monster_spawn_light_level: MonsterSpawnLightLevel::Constant(7),
+            monster_spawn_block_light_limit: 15,
+        }
+    }
+
+    pub fn the_end() -> Self {
+        Self {
+            fixed_time: Some(6000),
+            has_skylight: false,
+            has_ceiling: false,
+            ultrawarm: false,
+            natural: false,
+            coordinate_scale: 1.0,
+            bed_works: false,
+            respawn_anchor_works: false,
+            min_y: 0,
+            height: 256,
+            logical_height: 256,
+            infiniburn: String::from("#minecraft:infiniburn_end"),
+            effects: minecraft("the_end"),
+            ambient_light: 0.0,
+            piglin_safe: false,
+            has_raids: true,
+            monster_spawn_light_level: MonsterSpawnLightLevel::Uniform {
+                min_inclusive: 0,
+                max_inclusive: 7,
+            },
+            monster_spawn_block_light_limit: 0,
+        }
+    }
+
+    pub fn vanilla() -> Registry<Self> {
+        Registry::new(minecraft("dimension_type"))
+            .with(minecraft("overworld"), Self::overworld())
+            .with(minecraft("overworld_caves"), Self::overworld_caves())
+            .with(minecraft("the_end"), Self::the_end())
+            .with(minecraft("the_nether"), Self::the_nether())
+    }
+}
+
+/// The light level at or below which monsters may spawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonsterSpawnLightLevel {
+    Constant(i32),
+    /// Picked at random for each attempt.
+    Uniform {
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+}
+
+impl Serialize for MonsterSpawnLightLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Range {
+            min_inclusive: i32,
+            max_inclusive: i32,
+        }
+
+        #[derive(Serialize)]
+        struct Uniform {
+            #[serde(rename = "type")]
+            kind: &'static str,
+            value: Range,
+        }
+
+        match *self {
+            Self::Constant(level) => serializer.serialize_i32(level),
+            Self::Uniform {
+                min_inclusive,
+                max_inclusive,
+            } => Uniform {
+                kind: "minecraft:uniform",
+                value: Range {
+                    min_inclusive,
+                    max_inclusive,
+                },
+            }
+            .serialize(serializer),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! The registries sent in Login (play), which the client needs to agree with the server on
+//! before it can join.
+
+use serde::{
+    ser::{SerializeMap, SerializeStruct},
+    Serialize, Serializer,
+};
+
+use crate::nbt::{self, Compound};
+
+use super::*;
+
+mod biome;
+mod chat;
+mod damage;
+mod dimension;
+mod trim;
+
+pub use biome::*;
+pub use chat::*;
+pub use damage::*;
+pub use dimension::*;
+pub use trim::*;
+
+/// A name in the `minecraft` namespace, which vanilla entries are all known to have valid.
+fn minecraft(path: &str) -> Identifier {
+    Identifier::minecraft(path).expect("vanilla names are valid")
+}
+
+/// Named entries of one kind, whose ids are the order they were added in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registry<T> {
+    kind: Identifier,
+    entries: Vec<(Identifier, T)>,
+}
+
+impl<T> Registry<T> {
+    /// An empty registry such as `minecraft:worldgen/biome`.
+    pub fn new(kind: Identifier) -> Self {
+        Self {
+            kind,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn kind(&self) -> &Identifier {
+        &self.kind
+    }
+
+    /// Adds `element` as `name` and returns its id. An entry with the same name is replaced
+    /// in place, keeping its id.
+    pub fn insert(&mut self, name: Identifier, element: T) -> i32 {
+        let id = match self.entries.iter().position(|(entry, _)| *entry == name) {
+            Some(id) => {
+                self.entries[id].1 = element;
+                id
+            }
+            None => {
+                self.entries.push((name, element));
+                self.entries.len() - 1
+            }
+        };
+
+        id as i32
+    }
+
+    /// Like [`Registry::insert`], for chaining.
+    pub fn with(mut self, name: Identifier, element: T) -> Self {
+        self.insert(name, element);
+        self
+    }
+
+    pub fn get(&self, name: &Identifier) -> Option<&T> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| entry == name)
+            .map(|(_, element)| element)
+    }
+
+    /// The id the client knows `name` by.
+    pub fn id(&self, name: &Identifier) -> Option<i32> {
+        self.entries
+            .iter()
+            .position(|(entry, _)| entry == name)
+            .map(|id| id as i32)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Identifier, &T)> {
+        self.entries.iter().map(|(name, element)| (name, element))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T: Serialize> Serialize for Registry<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Entry<'a, T> {
+            name: &'a Identifier,
+            id: i32,
+            element: &'a T,
+        }
+
+        let value: Vec<_> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, (name, element))| Entry {
+                name,
+                id: id as i32,
+                element,
+            })
+            .collect();
+
+        let mut registry = serializer.serialize_struct("Registry", 2)?;
+        registry.serialize_field("type", &self.kind)?;
+        registry.serialize_field("value", &value)?;
+        registry.end()
+    }
+}
+
+/// Every registry a 1.20.1 client expects in Login (play).
+///
+/// Serializes into the registry codec, keyed by each registry's kind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registries {
+    pub dimension_types: Registry<DimensionType>,
+    pub biomes: Registry<Biome>,
+    pub chat_types: Registry<ChatType>,
+    pub damage_types: Registry<DamageType>,
+    pub trim_materials: Registry<TrimMaterial>,
+    pub trim_patterns: Registry<TrimPattern>,
+}
+
+impl Registries {
+    /// The vanilla entries, leaving out the biomes a server without terrain generation has
+    /// no use for.
+    ///
+    /// The client looks up every vanilla damage type by name, so none of them may be missing.
+    pub fn vanilla() -> Self {
+        Self {
+            dimension_types: DimensionType::vanilla(),
+            biomes: Biome::vanilla(),
+            chat_types: ChatType::vanilla(),
+            damage_types: DamageType::vanilla(),
+            trim_materials: TrimMaterial::vanilla(),
+            trim_patterns: TrimPattern::vanilla(),
+        }
+    }
+
+    /// Adds or replaces a dimension type, such as one for a void world with a fixed time.
+    pub fn with_dimension_type(mut self, name: Identifier, dimension_type: DimensionType) -> Self {
+        self.dimension_types.insert(name, dimension_type);
+        self
+    }
+
+    pub fn with_biome(mut self, name: Identifier, biome: Biome) -> Self {
+        self.biomes.insert(name, biome);
+        self
+    }
+
+    pub fn with_chat_type(mut self, name: Identifier, chat_type: ChatType) -> Self {
+        self.chat_types.insert(name, chat_type);
+        self
+    }
+
+    pub fn with_damage_type(mut self, name: Identifier, damage_type: DamageType) -> Self {
+        self.damage_types.insert(name, damage_type);
+        self
+    }
+
+    /// The registry codec, as sent in Login (play).
+    pub fn codec(&self) -> Result<Compound> {
+        nbt::to_compound(self)
+    }
+}
+
+impl Default for Registries {
+    fn default() -> Self {
+        Self::vanilla()
+    }
+}
+
+impl Serialize for Registries {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(6))?;
+        map.serialize_entry(self.dimension_types.kind(), &self.dimension_types)?;
+        map.serialize_entry(self.biomes.kind(), &self.biomes)?;
+        map.serialize_entry(self.chat_types.kind(), &self.chat_types)?;
+        map.serialize_entry(self.damage_types.kind(), &self.damage_types)?;
+        map.serialize_entry(self.trim_materials.kind(), &self.trim_materials)?;
+        map.serialize_entry(self.trim_patterns.kind(), &self.trim_patterns)?;
+        map.end()
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use crate::model::{Color, TextComponent};
+
+use super::*;
+
+/// What an armor trim is made of, which decides its color.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrimMaterial {
+    /// The suffix of the trim's texture palette.
+    pub asset_name: String,
+    /// The item used in the smithing table.
+    pub ingredient: Identifier,
+    /// Picks the trim's item model.
+    pub item_model_index: f32,
+    /// Palettes to use instead on armor of these materials, keyed by the armor's material.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub override_armor_materials: BTreeMap<String, String>,
+    pub description: TextComponent,
+}
+
+impl TrimMaterial {
+    pub fn vanilla() -> Registry<Self> {
+        // name, ingredient, color, and the armor material it has a darker palette for
+        let materials = [
+            ("quartz", "quartz", 0xe3d4c4, None),
+            ("iron", "iron_ingot", 0xececec, Some("iron")),
+            ("netherite", "netherite_ingot", 0x625859, Some("netherite")),
+            ("redstone", "redstone", 0x971607, None),
+            ("copper", "copper_ingot", 0xb4684d, None),
+            ("gold", "gold_ingot", 0xdeb12d, Some("gold")),
+            ("emerald", "emerald", 0x11a036, None),
+            ("diamond", "diamond", 0x6eecd2, Some("diamond")),
+            ("lapis", "lapis_lazuli", 0x416e97, None),
+            ("amethyst", "amethyst_shard", 0x9a5cc6, None),
+        ];
+
+        let mut registry = Registry::new(minecraft("trim_material"));
+        for (index, (name, ingredient, color, darker)) in materials.into_iter().enumerate() {
+            let [_, r, g, b] = u32::to_be_bytes(color);
+
+            registry.insert(
+                minecraft(name),
+                Self {
+                    asset_name: String::from(name),
+                    ingredient: minecraft(ingredient),
+                    item_model_index: (index + 1) as f32 / 10.0,
+                    override_armor_materials: darker
+                        .map(|armor| (String::from(armor), format!("{name}_darker")))
+                        .into_iter()
+                        .collect(),
+                    description: TextComponent::translate(format!(
+                        "trim_material.minecraft.{name}"
+                    ))
+                    .color(Color::Rgb(r, g, b)),
+                },
+            );
+        }
+        registry
+    }
+}
+
+/// The shape of an armor trim.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrimPattern {
+    /// Names the trim's textures.
+    pub asset_id: Identifier,
+    /// The smithing template that applies it.
+    pub template_item: Identifier,
+    pub description: TextComponent,
+}
+
+impl TrimPattern {
+    pub fn vanilla() -> Registry<Self> {
+        let patterns = [
+            "coast",
+            "dune",
+            "eye",
+            "host",
+            "raiser",
+            "rib",
+            "sentry",
+            "shaper",
+            "silence",
+            "snout",
+            "spire",
+            "tide",
+            "vex",
+            "ward",
+            "wayfinder",
+            "wild",
+        ];
+
+        let mut registry = Registry::new(minecraft("trim_pattern"));
+        for name in patterns {
+            registry.insert(
+                minecraft(name),
+                Self {
+                    asset_id: minecraft(name),
+                    template_item: minecraft(&format!("{name}_armor_trim_smithing_template")),
+                    description: TextComponent::translate(format!("trim_pattern.minecraft.{name}")),
+                },
+            );
+        }
+        registry
+    }
+}
@@ -0,0 +1,190 @@
+use arte_protocol::{
+    fundamental::{
+        with_decode_budget, BoundedVec, DecodeBudget, FrameLimits, PacketData, ReadExt,
+        SerializedPacket, VarInt, MAX_COLLECTION_LENGTH, MAX_STRING_LENGTH,
+    },
+    phases::login::{ClientboundLogin, LoginSuccess},
+    *,
+};
+use async_std::task;
+use bytes::{Bytes, BytesMut};
+
+fn encode(value: &impl Encode) -> Bytes {
+    let mut data = BytesMut::new();
+    value.encode(&mut data).unwrap();
+    data.freeze()
+}
+
+#[test]
+fn bounded_vec_checks_its_length() {
+    task::block_on(async {
+        let data = encode(&vec![1u8, 2, 3]);
+
+        assert_eq!(
+            BoundedVec::<u8, 3>::decode(&mut data.clone()).unwrap().0,
+            [1, 2, 3]
+        );
+        assert!(matches!(
+            BoundedVec::<u8, 2>::decode(&mut data.clone()),
+            Err(Error::CollectionTooLarge { length: 3, max: 2 })
+        ));
+        assert!(matches!(
+            (&data[..]).deserialize::<BoundedVec<u8, 2>>().await,
+            Err(Error::CollectionTooLarge { length: 3, max: 2 })
+        ));
+
+        let mut written = vec![];
+        assert!(matches!(
+            BoundedVec::<u8, 2>(vec![1, 2, 3])
+                .write_to(&mut written)
+                .await,
+            Err(Error::CollectionTooLarge { length: 3, max: 2 })
+        ));
+        assert!(matches!(
+            BoundedVec::<u8, 2>(vec![1, 2, 3]).encode(&mut BytesMut::new()),
+            Err(Error::CollectionTooLarge { length: 3, max: 2 })
+        ));
+    })
+}
+
+#[test]
+fn lengths_past_the_end_of_the_frame_are_rejected() {
+    // claims a billion items with four bytes to go
+    let mut data = BytesMut::new();
+    VarInt(1_000_000_000).encode(&mut data).unwrap();
+    data.extend_from_slice(&[0; 4]);
+
+    assert!(matches!(
+        Vec::<u64>::decode(&mut data.freeze()),
+        Err(Error::CollectionTooLarge {
+            length: 1_000_000_000,
+            max: 4
+        })
+    ));
+}
+
+#[test]
+fn async_reads_cap_collection_lengths() {
+    task::block_on(async {
+        let mut data = BytesMut::new();
+        VarInt(1_000_000_000).encode(&mut data).unwrap();
+        data.extend_from_slice(&[0; 4]);
+
+        assert!(matches!(
+            (&data[..]).deserialize::<Vec<u64>>().await,
+            Err(Error::CollectionTooLarge {
+                length: 1_000_000_000,
+                max: MAX_COLLECTION_LENGTH
+            })
+        ));
+
+        // within the cap, running out of data is an error rather than an allocation
+        let mut data = BytesMut::new();
+        VarInt::<i32>::usize(MAX_COLLECTION_LENGTH)
+            .encode(&mut data)
+            .unwrap();
+        data.extend_from_slice(&[0; 4]);
+        assert!(matches!(
+            (&data[..]).deserialize::<Vec<Vec<u8>>>().await,
+            Err(Error::Io(_))
+        ));
+    })
+}
+
+#[test]
+fn strings_default_to_the_protocol_limit() {
+    task::block_on(async {
+        let data = encode(&"a".repeat(MAX_STRING_LENGTH + 1));
+
+        assert!(matches!(
+            String::decode(&mut data.clone()),
+            Err(Error::StringTooLarge {
+                limit: MAX_STRING_LENGTH,
+                ..
+            })
+        ));
+        assert!(matches!(
+            (&data[..]).deserialize::<String>().await,
+            Err(Error::StringTooLarge {
+                limit: MAX_STRING_LENGTH,
+                ..
+            })
+        ));
+    })
+}
+
+#[test]
+fn decoding_spends_the_budget() {
+    // every empty inner vec is one byte on the wire, but a whole `Vec` in memory
+    let mut data = BytesMut::new();
+    VarInt(1000).encode(&mut data).unwrap();
+    data.extend_from_slice(&[0; 1000]);
+    let data = data.freeze();
+
+    assert!(matches!(
+        with_decode_budget(&mut 1024, || Vec::<Vec<u8>>::decode(&mut data.clone())),
+        Err(Error::DecodeBudgetExceeded { .. })
+    ));
+
+    let mut budget = 64 * 1024;
+    assert_eq!(
+        with_decode_budget(&mut budget, || Vec::<Vec<u8>>::decode(&mut data.clone()))
+            .unwrap()
+            .len(),
+        1000
+    );
+    // what's left is handed back
+    assert_eq!(budget, 64 * 1024 - 1000 * size_of::<Vec<u8>>());
+    // and there's no budget outside of it
+    assert!(Vec::<Vec<u8>>::decode(&mut data.clone()).is_ok());
+}
+
+#[test]
+fn connections_spend_one_budget_across_frames() {
+    task::block_on(async {
+        let success = encode(&LoginSuccess {
+            uuid: Uuid::nil(),
+            username: String::from("Notch"),
+            properties: vec![],
+        });
+        let mut frame = vec![];
+        SerializedPacket::Uncompressed(PacketData {
+            packet_id: LoginSuccess::ID,
+            data: success,
+        })
+        .write_to(&mut frame, None)
+        .await
+        .unwrap();
+
+        let read = |budget: &mut DecodeBudget| {
+            task::block_on(SerializedPacket::read_packet::<ClientboundLogin>(
+                &mut &frame[..],
+                None,
+                &FrameLimits::default(),
+                budget,
+                State::Login,
+            ))
+        };
+
+        // the username alone takes 5
+        assert!(matches!(
+            read(&mut DecodeBudget::new(4)),
+            Err(Error::DecodeBudgetExceeded {
+                needed: 5,
+                remaining: 4
+            })
+        ));
+
+        // it refills at 8 bytes a second, far too slow to matter for the second frame
+        let mut budget = DecodeBudget::new(8);
+        assert!(read(&mut budget).is_ok());
+        assert_eq!(budget.remaining(), 3);
+        assert!(matches!(
+            read(&mut budget),
+            Err(Error::DecodeBudgetExceeded {
+                needed: 5,
+                remaining: 3
+            })
+        ));
+    })
+}
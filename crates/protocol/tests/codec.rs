@@ -0,0 +1,174 @@
+use arte_protocol::{
+    fundamental::{FrameLimits, PacketData, ReadExt, SerializedPacket, StringLimit, VarInt, Zlib},
+    phases::{
+        handshaking::{ForwardedHandshake, Handshake, ServerboundHandshaking},
+        login::{LoginPluginResponse, LoginStart, Property, ServerboundLogin},
+    },
+    *,
+};
+use async_std::task;
+use bytes::{Bytes, BytesMut};
+use std::io::ErrorKind;
+
+fn encode(value: &impl Encode) -> Bytes {
+    let mut data = BytesMut::new();
+    value.encode(&mut data).unwrap();
+    data.freeze()
+}
+
+/// Checks that the sync codec agrees with the async one both ways.
+fn assert_same_as_async<T: AsyncSerialize + AsyncDeserialize + Encode + Decode>(value: T) -> T {
+    task::block_on(async {
+        let mut data = vec![];
+        value.write_to(&mut data).await.unwrap();
+        assert_eq!(encode(&value), data);
+
+        let decoded = T::decode(&mut Bytes::from(data.clone())).unwrap();
+        let read: T = (&data[..]).deserialize().await.unwrap();
+        assert_eq!(encode(&decoded), encode(&read));
+        decoded
+    })
+}
+
+#[test]
+fn fundamentals_match_the_async_codec() {
+    assert_eq!(assert_same_as_async(0x0102u16), 0x0102);
+    assert_eq!(assert_same_as_async(-5i64), -5);
+    assert!(assert_same_as_async(true));
+    assert_eq!(assert_same_as_async(VarInt(300)), VarInt(300));
+    assert_eq!(assert_same_as_async(VarInt(-300i64)), VarInt(-300));
+    assert_eq!(assert_same_as_async(String::from("arte")), "arte");
+    assert_eq!(
+        assert_same_as_async(StringLimit::<4>(String::from("arte"))).0,
+        "arte"
+    );
+    assert_eq!(assert_same_as_async(Uuid::max()), Uuid::max());
+    assert_eq!(assert_same_as_async(Some(7u8)), Some(7));
+    assert_eq!(assert_same_as_async(None::<u8>), None);
+    assert_eq!(assert_same_as_async(vec![1i32, 2, 3]), [1, 2, 3]);
+
+    let property = Property {
+        name: String::from("textures"),
+        value: String::from("e30="),
+        signature: Some(String::from("sig")),
+    };
+    assert_eq!(assert_same_as_async(property.clone()), property);
+}
+
+#[test]
+fn decode_fails_on_short_buffers() {
+    for data in [&[0x01][..], &[0x80], &[0x05, b'a'], &[0x01, 0x00, 0x00]] {
+        let decoded = String::decode(&mut &data[..]).and_then(|_| u32::decode(&mut &data[..]));
+        assert!(
+            matches!(decoded, Err(Error::Io(ref error)) if error.kind() == ErrorKind::UnexpectedEof),
+            "{data:?} gave {decoded:?}"
+        );
+    }
+}
+
+#[test]
+fn decode_enforces_string_limits() {
+    let data = encode(&String::from("too long"));
+
+    assert!(matches!(
+        StringLimit::<4>::decode(&mut data.clone()),
+        Err(Error::StringTooLarge {
+            length: 8,
+            limit: 4
+        })
+    ));
+}
+
+#[test]
+fn derived_packets_decode_with_context() {
+    let data = encode(&LoginStart {
+        username: String::from("Notch"),
+        uuid: Some(Uuid::nil()),
+    });
+
+    let packet = ServerboundLogin::decode_with_context(
+        &mut data.clone(),
+        &PacketContext {
+            id: LoginStart::ID,
+            state: State::Login,
+        },
+    )
+    .unwrap();
+    let ServerboundLogin::LoginStart(start) = packet else {
+        panic!("expected Login Start, got {packet:?}");
+    };
+    assert_eq!(start.username, "Notch");
+    assert_eq!(start.uuid, Some(Uuid::nil()));
+
+    let read = LoginPluginResponse::decode_with_context(
+        &mut data.clone(),
+        &PacketContext {
+            id: LoginPluginResponse::ID,
+            state: State::Play,
+        },
+    );
+    assert!(matches!(read, Err(Error::InvalidIdState { .. })));
+}
+
+#[test]
+fn handshake_keeps_its_address_limit() {
+    let handshake = Handshake {
+        protocol_version: PROTOCOL_VERSION,
+        address: "a".repeat(256),
+        port: 25565,
+        next_state: State::Status,
+    };
+    let context = PacketContext {
+        id: Handshake::ID,
+        state: State::Handshaking,
+    };
+
+    let read = ServerboundHandshaking::decode_with_context(&mut encode(&handshake), &context);
+    assert!(matches!(
+        read,
+        Err(Error::StringTooLarge {
+            length: 256,
+            limit: 255
+        })
+    ));
+
+    let read = ForwardedHandshake::decode_with_context(&mut encode(&handshake), &context);
+    assert_eq!(read.unwrap().0.address.len(), 256);
+}
+
+#[test]
+fn frames_round_trip_through_memory() {
+    task::block_on(async {
+        let data = PacketData {
+            packet_id: VarInt(0x04),
+            data: Bytes::from(vec![0x2a; 512]),
+        };
+
+        for (compressing, threshold) in [(false, None), (true, Some(256)), (true, Some(1024))] {
+            let packet = if compressing {
+                SerializedPacket::Compressed(Zlib(PacketData {
+                    packet_id: data.packet_id,
+                    data: data.data.clone(),
+                }))
+            } else {
+                SerializedPacket::Uncompressed(PacketData {
+                    packet_id: data.packet_id,
+                    data: data.data.clone(),
+                })
+            };
+
+            let mut frame = vec![];
+            packet.write_to(&mut frame, threshold).await.unwrap();
+            // a long run of the same byte has to shrink if it was deflated
+            assert_eq!(frame.len() < 512, threshold == Some(256));
+
+            let read =
+                SerializedPacket::read_from(&mut &frame[..], threshold, &FrameLimits::default())
+                    .await
+                    .unwrap()
+                    .into_data();
+            assert_eq!(read.packet_id, data.packet_id);
+            assert_eq!(read.data, data.data);
+        }
+    })
+}
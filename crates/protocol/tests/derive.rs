@@ -0,0 +1,207 @@
+use arte_protocol::{
+    fundamental::{ReadExt, VarInt, MAX_CHAT_LENGTH},
+    model::TextComponent,
+    phases::login::{Disconnect, LoginPluginResponse, LoginStart, LoginSuccess, Property},
+    *,
+};
+use async_std::task;
+use bytes::{Bytes, BytesMut};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Note {
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Packet)]
+#[packet(id = 0x42, state = Play)]
+struct Everything {
+    plain: u16,
+    #[varint]
+    count: i32,
+    #[limit(4)]
+    name: String,
+    #[json]
+    note: Note,
+    #[varint]
+    maybe: Option<i32>,
+    #[rest]
+    rest: Bytes,
+}
+
+#[derive(Debug, PartialEq, Packet)]
+#[packet(id = 0x00, state = Status)]
+struct Tuple(#[varint] i32, bool);
+
+async fn round_trip<T: AsyncSerialize + AsyncDeserializeContexful<Context = PacketContext>>(
+    packet: &T,
+    context: PacketContext,
+) -> (Vec<u8>, Result<T>) {
+    let mut data = vec![];
+    packet.write_to(&mut data).await.unwrap();
+
+    let read = (&data[..]).deserialize_with_context(&context).await;
+    (data, read)
+}
+
+fn everything() -> Everything {
+    Everything {
+        plain: 0x0102,
+        count: 300,
+        name: String::from("arte"),
+        note: Note {
+            text: String::from("hi"),
+        },
+        maybe: Some(1),
+        rest: Bytes::from_static(b"tail"),
+    }
+}
+
+#[test]
+fn derived_packet_round_trips() {
+    task::block_on(async {
+        let packet = everything();
+        let (data, read) = round_trip(
+            &packet,
+            PacketContext {
+                id: VarInt(0x42),
+                state: State::Play,
+            },
+        )
+        .await;
+
+        let json = br#"{"text":"hi"}"#;
+        let mut expected = vec![0x01, 0x02, 0xac, 0x02, 4];
+        expected.extend(b"arte");
+        expected.push(json.len() as u8);
+        expected.extend(json);
+        expected.extend([1, 1]);
+        expected.extend(b"tail");
+
+        assert_eq!(data, expected);
+        assert_eq!(read.unwrap(), packet);
+        assert_eq!(Everything::ID, VarInt(0x42));
+        assert_eq!(Everything::STATE, State::Play);
+    })
+}
+
+#[test]
+fn derived_packet_checks_context() {
+    task::block_on(async {
+        let (_, read) = round_trip(
+            &Tuple(-1, true),
+            PacketContext {
+                id: VarInt(0x01),
+                state: State::Status,
+            },
+        )
+        .await;
+
+        assert!(matches!(read, Err(Error::InvalidIdState { .. })));
+    })
+}
+
+#[test]
+fn derived_packet_enforces_limits_when_reading() {
+    task::block_on(async {
+        let mut data = vec![0x01, 0x02, 0xac, 0x02, 8];
+        data.extend(b"too long");
+
+        let read: Result<Everything> = (&data[..])
+            .deserialize_with_context(&PacketContext {
+                id: VarInt(0x42),
+                state: State::Play,
+            })
+            .await;
+
+        assert!(matches!(
+            read,
+            Err(Error::StringTooLarge {
+                length: 8,
+                limit: 4
+            })
+        ));
+    })
+}
+
+#[test]
+fn derived_packet_enforces_limits_when_writing() {
+    task::block_on(async {
+        let packet = Everything {
+            name: String::from("too long"),
+            ..everything()
+        };
+        let written = packet.write_to(&mut vec![]).await;
+        assert!(matches!(
+            written,
+            Err(Error::StringTooLarge {
+                length: 8,
+                limit: 4
+            })
+        ));
+        assert!(matches!(
+            packet.encode(&mut BytesMut::new()),
+            Err(Error::StringTooLarge { .. })
+        ));
+
+        let start = LoginStart {
+            username: String::from("a_very_long_username"),
+            uuid: None,
+        };
+        assert!(start.write_to(&mut vec![]).await.is_err());
+        assert!(start.encode(&mut BytesMut::new()).is_err());
+
+        // the limit applies to the JSON, quotes and all
+        let disconnect = Disconnect {
+            reason: TextComponent::text("a".repeat(MAX_CHAT_LENGTH)),
+        };
+        assert!(matches!(
+            disconnect.write_to(&mut vec![]).await,
+            Err(Error::StringTooLarge { .. })
+        ));
+        assert!(matches!(
+            disconnect.encode(&mut BytesMut::new()),
+            Err(Error::StringTooLarge { .. })
+        ));
+    })
+}
+
+#[test]
+fn login_packets_read_what_they_write() {
+    task::block_on(async {
+        let success = LoginSuccess {
+            uuid: Uuid::nil(),
+            username: String::from("Notch"),
+            properties: vec![Property {
+                name: String::from("textures"),
+                value: String::from("e30="),
+                signature: None,
+            }],
+        };
+        let (_, read) = round_trip(
+            &success,
+            PacketContext {
+                id: LoginSuccess::ID,
+                state: State::Login,
+            },
+        )
+        .await;
+        assert_eq!(read.unwrap().properties, success.properties);
+
+        for data in [None, Some(Bytes::from_static(b"payload"))] {
+            let response = LoginPluginResponse {
+                message_id: VarInt(3),
+                data,
+            };
+            let (_, read) = round_trip(
+                &response,
+                PacketContext {
+                    id: LoginPluginResponse::ID,
+                    state: State::Login,
+                },
+            )
+            .await;
+            assert_eq!(read.unwrap().data, response.data);
+        }
+    })
+}
@@ -0,0 +1,154 @@
+use arte_protocol::{
+    fundamental::{Identifier, Position, ReadExt, VarInt},
+    model::{ChatMode, Gamemode, MainHand, OptionalGamemode},
+    nbt::{Compound, Nbt},
+    phases::{
+        login::{LoginStart, ServerboundLogin},
+        play::{ClientInformation, ClientboundPlay, DeathLocation, LoginPlay, ServerboundPlay},
+    },
+    *,
+};
+use async_std::task;
+use bytes::Bytes;
+
+async fn encode(packet: &impl AsyncSerialize) -> Vec<u8> {
+    let mut data = vec![];
+    packet.write_to(&mut data).await.unwrap();
+    data
+}
+
+#[test]
+fn dispatches_on_id() {
+    task::block_on(async {
+        let data = encode(&LoginStart {
+            username: String::from("Notch"),
+            uuid: None,
+        })
+        .await;
+
+        let packet: ServerboundLogin = (&data[..])
+            .deserialize_with_context(&PacketContext {
+                id: LoginStart::ID,
+                state: State::Login,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(packet.id(), LoginStart::ID);
+        let ServerboundLogin::LoginStart(start) = packet else {
+            panic!("expected Login Start, got {packet:?}");
+        };
+        assert_eq!(start.username, "Notch");
+    })
+}
+
+#[test]
+fn dispatches_play_packets() {
+    task::block_on(async {
+        let information = ClientInformation {
+            locale: String::from("en_us"),
+            view_distance: 12,
+            chat_mode: ChatMode::CommandsOnly,
+            chat_colors: true,
+            displayed_skin_parts: 0x7f,
+            main_hand: MainHand::Left,
+            enable_text_filtering: false,
+            allow_server_listings: true,
+        };
+        let data = encode(&ServerboundPlay::from(information)).await;
+
+        let packet: ServerboundPlay = (&data[..])
+            .deserialize_with_context(&PacketContext {
+                id: ClientInformation::ID,
+                state: State::Play,
+            })
+            .await
+            .unwrap();
+
+        let ServerboundPlay::ClientInformation(information) = packet else {
+            panic!("expected Client Information, got {packet:?}");
+        };
+        assert_eq!(information.chat_mode, ChatMode::CommandsOnly);
+        assert_eq!(information.main_hand, MainHand::Left);
+    })
+}
+
+#[test]
+fn rejects_unknown_ids_and_states() {
+    task::block_on(async {
+        for (id, state) in [(VarInt(0x7f), State::Login), (LoginStart::ID, State::Play)] {
+            let read = (&[][..])
+                .deserialize_with_context::<ServerboundLogin>(&PacketContext { id, state })
+                .await;
+
+            assert!(matches!(
+                read,
+                Err(Error::UnknownPacket { id: got, state: got_state })
+                    if got == id && got_state == state
+            ));
+        }
+    })
+}
+
+#[test]
+fn login_play_reads_back() {
+    task::block_on(async {
+        let overworld = Identifier::minecraft("overworld").unwrap();
+        let nether = Identifier::minecraft("the_nether").unwrap();
+        let login = LoginPlay {
+            entity_id: 7,
+            is_hardcore: true,
+            game_mode: Gamemode::Creative,
+            previous_game_mode: OptionalGamemode(None),
+            dimension_names: vec![overworld.clone(), nether.clone()],
+            registry_codec: Nbt(Compound::new().with("answer", 42)),
+            dimension_type: overworld.clone(),
+            dimension_name: overworld.clone(),
+            hashed_seed: -3,
+            max_players: 20,
+            view_distance: 12,
+            simulation_distance: 8,
+            reduced_debug_info: false,
+            enable_respawn_screen: true,
+            is_debug: false,
+            is_flat: true,
+            death_location: Some(DeathLocation {
+                dimension_name: nether.clone(),
+                location: Position::new(-1, 2, -3),
+            }),
+            portal_cooldown: 300,
+        };
+
+        let data = encode(&login).await;
+        let mut encoded = bytes::BytesMut::new();
+        login.encode(&mut encoded).unwrap();
+        assert_eq!(encoded, data);
+
+        // entity id, hardcore, then a missing previous gamemode as -1
+        assert_eq!(data[..7], [0, 0, 0, 7, 1, 1, 0xff]);
+
+        let context = PacketContext {
+            id: LoginPlay::ID,
+            state: State::Play,
+        };
+        let read: ClientboundPlay = (&data[..])
+            .deserialize_with_context(&context)
+            .await
+            .unwrap();
+        let decoded =
+            ClientboundPlay::decode_with_context(&mut Bytes::from(data), &context).unwrap();
+
+        for packet in [read, decoded] {
+            let ClientboundPlay::LoginPlay(read) = packet else {
+                panic!("expected Login (play), got {packet:?}");
+            };
+            assert_eq!(read.game_mode, Gamemode::Creative);
+            assert_eq!(read.previous_game_mode, OptionalGamemode(None));
+            assert_eq!(read.dimension_names, [overworld.clone(), nether.clone()]);
+            assert_eq!(read.registry_codec, login.registry_codec);
+            assert_eq!(read.hashed_seed, -3);
+            assert_eq!(read.death_location, login.death_location);
+            assert_eq!(read.portal_cooldown, 300);
+        }
+    })
+}
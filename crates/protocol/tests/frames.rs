@@ -0,0 +1,149 @@
+use std::io::Write;
+
+use arte_protocol::{
+    fundamental::{FrameLimits, PacketData, SerializedPacket, VarInt, Zlib},
+    *,
+};
+use async_std::task;
+use bytes::BytesMut;
+use flate2::{write::ZlibEncoder, Compression};
+
+const THRESHOLD: Option<usize> = Some(256);
+
+/// Frames `body` with its length prefix.
+fn frame(body: &[u8]) -> Vec<u8> {
+    let mut frame = BytesMut::new();
+    VarInt::<i32>::usize(body.len()).encode(&mut frame).unwrap();
+    frame.extend_from_slice(body);
+    frame.to_vec()
+}
+
+/// A compressed frame claiming `data_length` but holding `data` deflated.
+fn compressed(data_length: i32, data: &[u8]) -> Vec<u8> {
+    let mut body = BytesMut::new();
+    VarInt(data_length).encode(&mut body).unwrap();
+
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(data).unwrap();
+    body.extend_from_slice(&encoder.finish().unwrap());
+
+    frame(&body)
+}
+
+fn read(frame: &[u8], threshold: Option<usize>, limits: FrameLimits) -> Result<SerializedPacket> {
+    task::block_on(SerializedPacket::read_from(
+        &mut &frame[..],
+        threshold,
+        &limits,
+    ))
+}
+
+#[test]
+fn negative_lengths_are_rejected() {
+    let mut frame = BytesMut::new();
+    VarInt(-1).encode(&mut frame).unwrap();
+
+    assert!(matches!(
+        read(&frame, None, FrameLimits::default()),
+        Err(Error::NegativeLength(-1))
+    ));
+    assert!(matches!(
+        read(&compressed(-5, b""), THRESHOLD, FrameLimits::default()),
+        Err(Error::NegativeLength(-5))
+    ));
+}
+
+#[test]
+fn oversized_frames_are_rejected_before_reading() {
+    let mut huge = BytesMut::new();
+    VarInt(i32::MAX).encode(&mut huge).unwrap();
+
+    assert!(matches!(
+        read(&huge, None, FrameLimits::default()),
+        Err(Error::FrameTooLarge {
+            length,
+            max: FrameLimits::MAX_FRAME_LENGTH
+        }) if length == i32::MAX as usize
+    ));
+
+    let limits = FrameLimits {
+        max_frame_length: 8,
+        ..FrameLimits::default()
+    };
+    assert!(matches!(
+        read(&frame(&[0; 9]), None, limits),
+        Err(Error::FrameTooLarge { length: 9, max: 8 })
+    ));
+    assert!(read(&frame(&[0; 8]), None, limits).is_ok());
+}
+
+#[test]
+fn compressed_data_length_is_checked() {
+    let data = [0x01; 512];
+
+    assert!(matches!(
+        read(
+            &compressed(100, &data[..100]),
+            THRESHOLD,
+            FrameLimits::default()
+        ),
+        Err(Error::BelowCompressionThreshold {
+            length: 100,
+            threshold: 256
+        })
+    ));
+
+    let limits = FrameLimits {
+        max_data_length: 300,
+        ..FrameLimits::default()
+    };
+    assert!(matches!(
+        read(&compressed(512, &data), THRESHOLD, limits),
+        Err(Error::DataTooLarge {
+            length: 512,
+            max: 300
+        })
+    ));
+
+    for claimed in [300, 1024] {
+        assert!(matches!(
+            read(&compressed(claimed, &data), THRESHOLD, FrameLimits::default()),
+            Err(Error::DataLengthMismatch { expected, .. }) if expected == claimed as usize
+        ));
+    }
+
+    let packet = read(&compressed(512, &data), THRESHOLD, FrameLimits::default()).unwrap();
+    assert_eq!(packet.into_data().data.len(), 511);
+}
+
+#[test]
+fn zlib_wrapper_does_not_inflate_bombs() {
+    let mut encoder = ZlibEncoder::new(vec![], Compression::best());
+    encoder
+        .write_all(&vec![0; FrameLimits::MAX_DATA_LENGTH + 1])
+        .unwrap();
+    let bomb = encoder.finish().unwrap();
+
+    assert!(matches!(
+        Zlib::<PacketData>::decode(&mut &bomb[..]),
+        Err(Error::DataTooLarge { max, .. }) if max == FrameLimits::MAX_DATA_LENGTH
+    ));
+}
+
+#[test]
+fn garbage_is_an_error() {
+    // a data length above the threshold followed by something that isn't zlib
+    let mut body = BytesMut::new();
+    VarInt(300).encode(&mut body).unwrap();
+    body.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+
+    assert!(matches!(
+        read(&frame(&body), THRESHOLD, FrameLimits::default()),
+        Err(Error::Io(_))
+    ));
+    // cut off in the middle of the frame
+    assert!(matches!(
+        read(&frame(&[0; 16])[..8], None, FrameLimits::default()),
+        Err(Error::Io(_))
+    ));
+}
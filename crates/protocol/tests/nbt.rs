@@ -0,0 +1,325 @@
+use arte_protocol::{
+    fundamental::ReadExt,
+    nbt::{self, Compound, Nbt, NbtCompression, NbtLimits, Tag},
+    *,
+};
+use async_std::task;
+use bytes::BytesMut;
+use serde::{Deserialize, Serialize};
+
+/// `hello_world.nbt` from the original NBT specification.
+const HELLO_WORLD: &[u8] = b"\x0a\x00\x0bhello world\x08\x00\x04name\x00\x09Bananrama\x00";
+
+#[test]
+fn decodes_and_encodes_named_roots() {
+    let (name, root) =
+        Compound::decode_named(&mut &HELLO_WORLD[..], &NbtLimits::default()).unwrap();
+    assert_eq!(name, "hello world");
+    assert_eq!(root, Compound::new().with("name", "Bananrama"));
+
+    let mut encoded = BytesMut::new();
+    root.encode_named(&name, &mut encoded).unwrap();
+    assert_eq!(encoded, HELLO_WORLD);
+}
+
+#[test]
+fn network_roots_have_no_name() {
+    let root = Compound::new()
+        .with("id", 7i32)
+        .with("tags", vec![Tag::from("a"), Tag::from("b")]);
+
+    let mut encoded = BytesMut::new();
+    root.encode_network(&mut encoded).unwrap();
+    // straight from the root's type to its first entry
+    assert_eq!(encoded[..5], [nbt::COMPOUND, nbt::INT, 0, 2, b'i']);
+
+    let decoded = Compound::decode_network(&mut encoded.freeze(), &NbtLimits::default()).unwrap();
+    assert_eq!(decoded, root);
+}
+
+#[test]
+fn rejects_mixed_lists() {
+    let root = Compound::new().with("list", vec![Tag::Int(1), Tag::Byte(1)]);
+    assert!(matches!(
+        root.encode_named("", &mut BytesMut::new()),
+        Err(Error::Nbt(_))
+    ));
+    assert!(matches!("[1, 1b]".parse::<Tag>(), Err(Error::Nbt(_))));
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Weather {
+    Clear,
+    Rain,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Position {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Player {
+    name: String,
+    health: f32,
+    level: i32,
+    seed: i64,
+    food: i16,
+    slot: u8,
+    on_ground: bool,
+    weather: Weather,
+    spawn: Option<Position>,
+    last_death: Option<Position>,
+    recipes: Vec<String>,
+    #[serde(with = "nbt::int_array")]
+    uuid: Vec<i32>,
+    #[serde(with = "nbt::long_array")]
+    heightmap: Vec<i64>,
+    #[serde(with = "nbt::byte_array")]
+    skylight: Vec<i8>,
+    extra: Compound,
+}
+
+fn player() -> Player {
+    Player {
+        name: String::from("Steve"),
+        health: 20.0,
+        level: 30,
+        seed: -4_172_144_997_902_289_642,
+        food: 17,
+        slot: 255,
+        on_ground: true,
+        weather: Weather::Rain,
+        spawn: Some(Position {
+            x: 0.5,
+            y: 64.0,
+            z: -0.5,
+        }),
+        last_death: None,
+        recipes: vec![String::from("minecraft:torch")],
+        uuid: vec![1, -2, 3, -4],
+        heightmap: vec![i64::MIN, 0, i64::MAX],
+        skylight: vec![-1, 0, 15],
+        extra: Compound::new().with("tag", vec![1i64, 2]),
+    }
+}
+
+#[test]
+fn typed_structs_round_trip() {
+    let player = player();
+    let root = nbt::to_compound(&player).unwrap();
+
+    assert_eq!(root["on_ground"], Tag::Byte(1));
+    assert_eq!(root["slot"], Tag::Byte(-1));
+    assert_eq!(root["weather"], Tag::from("rain"));
+    assert_eq!(root["uuid"], Tag::IntArray(vec![1, -2, 3, -4]));
+    assert_eq!(root["skylight"], Tag::ByteArray(vec![-1, 0, 15]));
+    assert!(!root.contains_key("last_death"));
+
+    let mut encoded = BytesMut::new();
+    root.encode_named("", &mut encoded).unwrap();
+    let (_, decoded) =
+        Compound::decode_named(&mut encoded.freeze(), &NbtLimits::default()).unwrap();
+    assert_eq!(decoded, root);
+
+    assert_eq!(nbt::from_compound::<Player>(decoded).unwrap(), player);
+}
+
+#[test]
+fn nbt_in_packets() {
+    let player = player();
+
+    let mut encoded = BytesMut::new();
+    Nbt(&player).encode(&mut encoded).unwrap();
+    encoded.extend_from_slice(b"next field");
+
+    let mut buf = encoded.clone().freeze();
+    assert_eq!(Nbt::<Player>::decode(&mut buf).unwrap().0, player);
+    assert_eq!(buf, &b"next field"[..]);
+
+    task::block_on(async {
+        let mut written = vec![];
+        Nbt(&player).write_to(&mut written).await.unwrap();
+        assert_eq!(written, encoded[..written.len()]);
+
+        let mut reader = &encoded[..];
+        let read: Nbt<Player> = reader.deserialize().await.unwrap();
+        assert_eq!(read.0, player);
+        assert_eq!(reader, b"next field");
+    })
+}
+
+#[test]
+fn strings_use_modified_utf8() {
+    let string = "nul\0 and \u{1f600}";
+    let encoded = nbt::encode_mutf8(string);
+
+    assert!(!encoded.contains(&0));
+    assert!(encoded.windows(2).any(|pair| pair == [0xc0, 0x80]));
+    // each half of the surrogate pair takes three bytes
+    assert_eq!(encoded.len(), string.len() + 1 + 2);
+    assert_eq!(nbt::decode_mutf8(&encoded).unwrap(), string);
+
+    assert!(nbt::decode_mutf8(b"\xed\xa0\xbd").is_err());
+    assert_eq!(nbt::decode_mutf8("plain ü".as_bytes()).unwrap(), "plain ü");
+}
+
+#[test]
+fn parses_snbt() {
+    let tag: Compound = r#"{
+        name: "Steve", 'odd key': 'say "hi"', pos: [I; 1, -2, 3], seed: 10L,
+        flags: [B; 1b, 0b], longs: [L; 5L], ratio: 0.5f, scale: 2.d, plain: 1.5,
+        nested: {list: [1s, 2s], empty: []}, on: true, word: stone
+    }"#
+    .parse()
+    .unwrap();
+
+    let expected = Compound::new()
+        .with("name", "Steve")
+        .with("odd key", r#"say "hi""#)
+        .with("pos", vec![1, -2, 3])
+        .with("seed", 10i64)
+        .with("flags", vec![1i8, 0])
+        .with("longs", vec![5i64])
+        .with("ratio", 0.5f32)
+        .with("scale", 2.0)
+        .with("plain", 1.5)
+        .with(
+            "nested",
+            Compound::new()
+                .with("list", vec![Tag::Short(1), Tag::Short(2)])
+                .with("empty", Vec::<Tag>::new()),
+        )
+        .with("on", true)
+        .with("word", "stone");
+    assert_eq!(tag, expected);
+
+    let printed = Tag::Compound(tag).to_string();
+    assert_eq!(printed.parse::<Tag>().unwrap(), Tag::Compound(expected));
+}
+
+#[test]
+fn snbt_literals_follow_vanilla() {
+    let cases = [
+        ("1b", Tag::Byte(1)),
+        ("-7S", Tag::Short(-7)),
+        ("+5", Tag::Int(5)),
+        ("2147483648", Tag::from("2147483648")),
+        ("300b", Tag::from("300b")),
+        ("007", Tag::from("007")),
+        ("1e5", Tag::from("1e5")),
+        ("1e5f", Tag::Float(1e5)),
+        (".5", Tag::Double(0.5)),
+        ("3d", Tag::Double(3.0)),
+        ("false", Tag::Byte(0)),
+        ("\"a\\\\b\"", Tag::from("a\\b")),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(input.parse::<Tag>().unwrap(), expected, "{input}");
+    }
+
+    for input in ["", "{a:1", "[I; 1b]", "{a 1}", "\"open", "1 2"] {
+        assert!(input.parse::<Tag>().is_err(), "{input}");
+    }
+}
+
+/// A root compound holding `depth - 1` nested lists.
+fn nested_lists(depth: usize) -> Vec<u8> {
+    let mut data = b"\x0a\x00\x00\x09\x00\x01x".to_vec();
+    for _ in 2..depth {
+        data.extend_from_slice(b"\x09\x00\x00\x00\x01");
+    }
+    data.extend_from_slice(b"\x00\x00\x00\x00\x00");
+    data.extend_from_slice(b"\x00");
+    data
+}
+
+#[test]
+fn limits_depth() {
+    let limits = NbtLimits::default();
+
+    let fine = nested_lists(NbtLimits::MAX_DEPTH);
+    Compound::decode_named(&mut &fine[..], &limits).unwrap();
+    task::block_on(nbt::read_root_bytes(&mut &fine[..], true, &limits)).unwrap();
+
+    let deep = nested_lists(NbtLimits::MAX_DEPTH + 1);
+    assert!(matches!(
+        Compound::decode_named(&mut &deep[..], &limits),
+        Err(Error::NbtTooDeep(NbtLimits::MAX_DEPTH))
+    ));
+    assert!(matches!(
+        task::block_on(nbt::read_root_bytes(&mut &deep[..], true, &limits)),
+        Err(Error::NbtTooDeep(NbtLimits::MAX_DEPTH))
+    ));
+
+    let snbt = "[".repeat(NbtLimits::MAX_DEPTH + 1) + &"]".repeat(NbtLimits::MAX_DEPTH + 1);
+    assert!(matches!(
+        snbt.parse::<Tag>(),
+        Err(Error::NbtTooDeep(NbtLimits::MAX_DEPTH))
+    ));
+}
+
+#[test]
+fn limits_size() {
+    // an int array claiming i32::MAX items, with none of them there
+    let huge = b"\x0a\x00\x00\x0b\x00\x01a\x7f\xff\xff\xff";
+    assert!(matches!(
+        Compound::decode_named(&mut &huge[..], &NbtLimits::default()),
+        Err(Error::Io(_))
+    ));
+    assert!(matches!(
+        task::block_on(nbt::read_root_bytes(
+            &mut &huge[..],
+            true,
+            &NbtLimits::default()
+        )),
+        Err(Error::NbtTooLarge(_))
+    ));
+
+    let root = Compound::new().with("data", vec![0i64; 64]);
+    let mut encoded = BytesMut::new();
+    root.encode_named("", &mut encoded).unwrap();
+    let small = NbtLimits {
+        max_size: 256,
+        ..NbtLimits::default()
+    };
+    assert!(matches!(
+        Compound::decode_named(&mut &encoded[..], &small),
+        Err(Error::NbtTooLarge(256))
+    ));
+
+    let end_list = b"\x0a\x00\x00\x09\x00\x01l\x00\x00\x00\x00\x05\x00";
+    assert!(matches!(
+        Compound::decode_named(&mut &end_list[..], &NbtLimits::default()),
+        Err(Error::Nbt(_))
+    ));
+}
+
+#[test]
+fn files_round_trip() {
+    task::block_on(async {
+        let root = nbt::to_compound(&player()).unwrap();
+
+        for compression in [
+            NbtCompression::None,
+            NbtCompression::Gzip,
+            NbtCompression::Zlib,
+        ] {
+            let mut file = vec![];
+            nbt::write_file(&mut file, "Data", &root, compression)
+                .await
+                .unwrap();
+            assert_eq!(NbtCompression::detect(&file), compression);
+
+            let (name, read) = nbt::read_file(&mut &file[..], &NbtLimits::default())
+                .await
+                .unwrap();
+            assert_eq!(name, "Data");
+            assert_eq!(read, root);
+        }
+    })
+}
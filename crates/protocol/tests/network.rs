@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use arte_protocol::{
+    fundamental::{CipherStream, DecodeBudget, FrameLimits, SerializedPacket},
+    phases::play::{ClientboundPlay, ClientboundPluginMessage},
+    *,
+};
+use async_std::{
+    io::ReadExt,
+    net::{TcpListener, TcpStream},
+    task,
+};
+use bytes::Bytes;
+
+/// A client and the server's end of its connection, both in play.
+async fn connection(compression_threshold: Option<usize>) -> (PlayerNetwork, PlayerNetwork) {
+    let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = TcpStream::connect(tcp.local_addr().unwrap()).await.unwrap();
+    let (server, _) = tcp.accept().await.unwrap();
+
+    let network = |tcp| PlayerNetwork {
+        tcp: CipherStream::new(tcp),
+        compressing: compression_threshold.is_some(),
+        state: State::Play,
+        compression_threshold,
+        frame_limits: FrameLimits::default(),
+        decode_budget: DecodeBudget::default(),
+    };
+
+    (network(client), network(server))
+}
+
+fn message(channel: &str, data: impl Into<Bytes>) -> ClientboundPluginMessage {
+    ClientboundPluginMessage {
+        channel: String::from(channel),
+        data: data.into(),
+    }
+}
+
+async fn recv_message(client: &mut PlayerNetwork) -> ClientboundPluginMessage {
+    match client.recv_packet().await.unwrap() {
+        ClientboundPlay::PluginMessage(message) => message,
+        packet => panic!("expected a plugin message, got {packet:?}"),
+    }
+}
+
+#[test]
+fn sends_from_many_tasks_keep_their_order() {
+    task::block_on(async {
+        let (mut client, server) = connection(Some(64)).await;
+        let (_reader, sender, writer) = server.split(SendQueueLimits::default());
+        task::spawn(writer.run());
+
+        for task in 0..4 {
+            let sender = sender.clone();
+            task::spawn(async move {
+                for i in 0..100u32 {
+                    // long enough for some to be compressed
+                    let data = [i.to_be_bytes(); 32].concat();
+                    sender
+                        .send(message(&format!("test:{task}"), data))
+                        .await
+                        .unwrap();
+                }
+            });
+        }
+
+        let mut next = [0u32; 4];
+        for _ in 0..400 {
+            let message = recv_message(&mut client).await;
+            let task: usize = message.channel["test:".len()..].parse().unwrap();
+
+            assert_eq!(message.data[..4], next[task].to_be_bytes());
+            next[task] += 1;
+        }
+        assert_eq!(next, [100; 4]);
+    })
+}
+
+#[test]
+fn closing_sends_whats_queued_first() {
+    task::block_on(async {
+        let (mut client, server) = connection(None).await;
+        let (_reader, sender, writer) = server.split(SendQueueLimits::default());
+
+        for i in 0..10u8 {
+            sender.try_send(message("test:close", vec![i])).unwrap();
+        }
+        sender.close();
+        assert!(matches!(
+            sender.try_send(message("test:close", vec![])),
+            Err(Error::ConnectionClosed)
+        ));
+
+        // only starts writing now, so nothing was sent before the close
+        writer.run().await.unwrap();
+
+        for i in 0..10u8 {
+            assert_eq!(recv_message(&mut client).await.data[..], [i]);
+        }
+
+        let mut rest = vec![];
+        client.tcp.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+    })
+}
+
+#[test]
+fn frames_are_flushed_once_per_tick() {
+    task::block_on(async {
+        let (mut client, server) = connection(None).await;
+        let limits = SendQueueLimits {
+            flush_interval: Duration::from_millis(300),
+            ..SendQueueLimits::default()
+        };
+        let (_reader, sender, writer) = server.split(limits);
+        task::spawn(writer.run());
+
+        sender.send(message("test:first", vec![])).await.unwrap();
+        task::sleep(Duration::from_millis(100)).await;
+        sender.send(message("test:second", vec![])).await.unwrap();
+
+        // both wait in the writer's buffer for the tick to end
+        assert!(
+            async_std::future::timeout(Duration::from_millis(100), recv_message(&mut client))
+                .await
+                .is_err()
+        );
+        assert_eq!(recv_message(&mut client).await.channel, "test:first");
+        assert_eq!(recv_message(&mut client).await.channel, "test:second");
+    })
+}
+
+#[test]
+fn full_queue_disconnects_slow_clients() {
+    task::block_on(async {
+        let (mut client, server) = connection(None).await;
+        let limits = SendQueueLimits {
+            capacity: 4,
+            max_wait: Duration::from_secs(10),
+            ..SendQueueLimits::default()
+        };
+        let (_reader, sender, writer) = server.split(limits);
+        let writer = task::spawn(writer.run());
+
+        // the client never reads, so the socket's buffers fill up and then the queue does
+        let data = Bytes::from(vec![0; 1 << 20]);
+        let error = (0..1024)
+            .find_map(|_| sender.try_send(message("test:flood", data.clone())).err())
+            .expect("the queue never filled up");
+        assert!(matches!(error, Error::SendQueueFull(4)));
+
+        assert!(sender.is_closed());
+        assert!(matches!(
+            sender.try_send(message("test:flood", vec![])),
+            Err(Error::ConnectionClosed)
+        ));
+
+        // the connection is cut off rather than drained
+        let _ = writer.await;
+        let mut rest = vec![];
+        let _ = client.tcp.read_to_end(&mut rest).await;
+        assert!(rest.len() < 1024 << 20);
+    })
+}
+
+#[test]
+fn send_gives_up_after_waiting() {
+    task::block_on(async {
+        let (_client, server) = connection(None).await;
+        let limits = SendQueueLimits {
+            capacity: 1,
+            max_wait: Duration::from_millis(100),
+            ..SendQueueLimits::default()
+        };
+        let (_reader, sender, writer) = server.split(limits);
+        task::spawn(writer.run());
+
+        let data = Bytes::from(vec![0; 1 << 20]);
+        let mut result = Ok(());
+        for _ in 0..1024 {
+            result = sender.send(message("test:wait", data.clone())).await;
+            if result.is_err() {
+                break;
+            }
+        }
+
+        assert!(matches!(result, Err(Error::SendQueueFull(1))));
+        assert!(sender.is_closed());
+    })
+}
+
+#[test]
+fn encoded_packets_are_framed_once_per_setting() {
+    let packet = message("test:encoded", vec![7; 512]);
+    let encoded = EncodedPacket::new(&packet).unwrap();
+
+    for (compressing, threshold) in [(false, None), (true, None), (true, Some(64))] {
+        let frame = encoded.frame(compressing, threshold).unwrap();
+        assert_eq!(
+            frame,
+            SerializedPacket::encode_packet(&packet, compressing, threshold).unwrap()
+        );
+
+        // shared rather than encoded again, by clones too
+        let again = encoded.clone().frame(compressing, threshold).unwrap();
+        assert_eq!(frame.as_ptr(), again.as_ptr());
+    }
+
+    assert!(
+        encoded.frame(true, Some(64)).unwrap().len() < encoded.frame(false, None).unwrap().len()
+    );
+}
+
+#[test]
+fn encoded_packets_reach_every_connection() {
+    task::block_on(async {
+        let packet = EncodedPacket::new(&message("test:shared", vec![1; 300])).unwrap();
+
+        for threshold in [None, Some(256), Some(1024)] {
+            let (mut client, server) = connection(threshold).await;
+            let (_reader, sender, writer) = server.split(SendQueueLimits::default());
+            task::spawn(writer.run());
+
+            sender.send_encoded(&packet).await.unwrap();
+            sender.try_send_encoded(&packet).unwrap();
+
+            for _ in 0..2 {
+                let message = recv_message(&mut client).await;
+                assert_eq!(message.channel, "test:shared");
+                assert_eq!(message.data[..], [1; 300]);
+            }
+        }
+    })
+}
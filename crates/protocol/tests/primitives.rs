@@ -0,0 +1,187 @@
+use std::fmt::Debug;
+
+use arte_protocol::{
+    fundamental::{
+        Angle, BitSet, ByteArray, EnumSet, EnumSetType, FixedBitSet, Identifier, Position, ReadExt,
+        RemainingBytes,
+    },
+    *,
+};
+use async_std::task;
+use bytes::{Bytes, BytesMut};
+
+/// Writes `value` with both codecs, checks they agree on `expected`, and reads it back.
+fn assert_bytes<T>(value: T, expected: &[u8])
+where
+    T: AsyncSerialize + AsyncDeserialize + Encode + Decode + PartialEq + Debug,
+{
+    task::block_on(async {
+        let mut written = vec![];
+        value.write_to(&mut written).await.unwrap();
+        assert_eq!(written, expected, "writing {value:?}");
+
+        let mut encoded = BytesMut::new();
+        value.encode(&mut encoded).unwrap();
+        assert_eq!(encoded, expected, "encoding {value:?}");
+
+        assert_eq!((&expected[..]).deserialize::<T>().await.unwrap(), value);
+        assert_eq!(T::decode(&mut &expected[..]).unwrap(), value);
+    })
+}
+
+#[test]
+fn positions_pack_into_a_long() {
+    // from https://wiki.vg/Protocol#Position
+    assert_bytes(
+        Position::new(18357644, 831, -20882616),
+        &0x4607632c15b4833fu64.to_be_bytes(),
+    );
+
+    for position in [
+        Position::new(-1, -1, -1),
+        Position::new(-33554432, -2048, -33554432),
+        Position::new(33554431, 2047, 33554431),
+    ] {
+        assert_eq!(Position::unpack(position.pack()), position);
+    }
+    assert_eq!(Position::new(-1, -1, -1).pack(), u64::MAX);
+}
+
+#[test]
+fn angles_are_fractions_of_a_turn() {
+    assert_bytes(Angle(64), &[64]);
+
+    assert_eq!(Angle::from_degrees(90.0), Angle(64));
+    assert_eq!(Angle::from_degrees(-90.0), Angle(192));
+    assert_eq!(Angle::from_degrees(360.0), Angle(0));
+    assert_eq!(Angle(128).degrees(), 180.0);
+}
+
+#[test]
+fn floats_are_big_endian() {
+    assert_bytes(1.5f32, &[0x3f, 0xc0, 0x00, 0x00]);
+    assert_bytes(-2.0f64, &[0xc0, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn bitsets_are_arrays_of_longs() {
+    let mut set = BitSet::new();
+    set.set(0, true);
+    set.set(65, true);
+    set.set(200, false);
+
+    assert!(set.get(0) && set.get(65));
+    assert!(!set.get(1) && !set.get(200));
+
+    let mut expected = vec![2];
+    expected.extend(1u64.to_be_bytes());
+    expected.extend(2u64.to_be_bytes());
+    assert_bytes(set, &expected);
+}
+
+#[test]
+fn fixed_bitsets_have_no_prefix() {
+    let mut set = FixedBitSet::<3>::new();
+    set.set(0, true);
+    set.set(9, true);
+    set.set(23, true);
+
+    assert!(set.get(9) && !set.get(8));
+    assert_bytes(set, &[0x01, 0x02, 0x80]);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Part {
+    Cape,
+    Jacket,
+    Hat,
+}
+
+impl EnumSetType for Part {
+    const VARIANTS: usize = 3;
+
+    fn ordinal(self) -> usize {
+        self as usize
+    }
+
+    fn from_ordinal(ordinal: usize) -> Option<Self> {
+        [Self::Cape, Self::Jacket, Self::Hat].get(ordinal).copied()
+    }
+}
+
+#[test]
+fn enum_sets_are_fixed_bitsets() {
+    let set: EnumSet<Part> = [Part::Cape, Part::Hat].into_iter().collect();
+
+    assert!(set.contains(Part::Hat) && !set.contains(Part::Jacket));
+    assert_eq!(set.iter().collect::<Vec<_>>(), [Part::Cape, Part::Hat]);
+    assert_bytes(set, &[0b101]);
+
+    // bits past the last variant are ignored
+    assert_eq!(
+        EnumSet::<Part>::decode(&mut &[0xff][..])
+            .unwrap()
+            .iter()
+            .count(),
+        3
+    );
+}
+
+#[test]
+fn identifiers_are_validated() {
+    let stone: Identifier = "stone".parse().unwrap();
+    assert_eq!(stone, Identifier::minecraft("stone").unwrap());
+    assert_eq!(stone.to_string(), "minecraft:stone");
+
+    let custom: Identifier = "arte:block/lamp_1.json".parse().unwrap();
+    assert_eq!(custom.namespace(), "arte");
+    assert_eq!(custom.path(), "block/lamp_1.json");
+
+    let mut expected = vec![15];
+    expected.extend(b"minecraft:stone");
+    assert_bytes(stone.clone(), &expected);
+
+    assert_eq!(":stone".parse::<Identifier>().unwrap(), stone);
+
+    for invalid in ["Stone", "ar/te:stone", "arte:sto ne", "a:b:c"] {
+        assert!(
+            matches!(
+                invalid.parse::<Identifier>(),
+                Err(Error::InvalidIdentifier(_))
+            ),
+            "{invalid} should be invalid"
+        );
+    }
+
+    let mut data = vec![5];
+    data.extend(b"BAD:x");
+    assert!(matches!(
+        Identifier::decode(&mut &data[..]),
+        Err(Error::InvalidIdentifier(_))
+    ));
+
+    assert_eq!(
+        serde_json::to_string(&custom).unwrap(),
+        r#""arte:block/lamp_1.json""#
+    );
+    assert_eq!(
+        serde_json::from_str::<Identifier>(r#""stone""#).unwrap(),
+        Identifier::minecraft("stone").unwrap()
+    );
+}
+
+#[test]
+fn byte_arrays() {
+    assert_bytes(ByteArray(Bytes::from_static(b"abc")), b"\x03abc");
+    assert_bytes(RemainingBytes(Bytes::from_static(b"abc")), b"abc");
+
+    // claims more than there is
+    assert!(matches!(
+        ByteArray::decode(&mut &b"\x05ab"[..]),
+        Err(Error::Io(_))
+    ));
+    assert!(matches!(
+        task::block_on((&b"\x05ab"[..]).deserialize::<ByteArray>()),
+        Err(Error::Io(_))
+    ));
+}
@@ -0,0 +1,200 @@
+use arte_protocol::{
+    fundamental::Identifier,
+    nbt::{Compound, NbtLimits, Tag},
+    registry::{Biome, DamageType, DimensionType, MonsterSpawnLightLevel, Registries, Registry},
+};
+use bytes::BytesMut;
+
+fn entries<'a>(codec: &'a Compound, kind: &str) -> &'a [Tag] {
+    let registry = codec[kind].as_compound().unwrap();
+    assert_eq!(registry["type"].as_str(), Some(kind));
+    match &registry["value"] {
+        Tag::List(entries) => entries,
+        other => panic!("expected a list of entries, got {other:?}"),
+    }
+}
+
+fn element<'a>(codec: &'a Compound, kind: &str, name: &str) -> &'a Compound {
+    entries(codec, kind)
+        .iter()
+        .map(|entry| entry.as_compound().unwrap())
+        .find(|entry| entry["name"].as_str() == Some(name))
+        .unwrap_or_else(|| panic!("{name} is missing from {kind}"))["element"]
+        .as_compound()
+        .unwrap()
+}
+
+#[test]
+fn vanilla_codec_has_every_registry() {
+    let codec = Registries::vanilla().codec().unwrap();
+
+    let kinds: Vec<_> = codec.keys().map(String::as_str).collect();
+    assert_eq!(
+        kinds,
+        [
+            "minecraft:chat_type",
+            "minecraft:damage_type",
+            "minecraft:dimension_type",
+            "minecraft:trim_material",
+            "minecraft:trim_pattern",
+            "minecraft:worldgen/biome",
+        ]
+    );
+
+    for (id, entry) in entries(&codec, "minecraft:dimension_type")
+        .iter()
+        .enumerate()
+    {
+        let entry = entry.as_compound().unwrap();
+        assert_eq!(entry["id"], Tag::Int(id as i32));
+        assert!(entry["name"].as_str().unwrap().starts_with("minecraft:"));
+        assert!(entry["element"].as_compound().is_some());
+    }
+
+    assert_eq!(entries(&codec, "minecraft:damage_type").len(), 44);
+    element(&codec, "minecraft:worldgen/biome", "minecraft:plains");
+}
+
+#[test]
+fn dimension_types_serialize_like_vanilla() {
+    let codec = Registries::vanilla().codec().unwrap();
+
+    let overworld = element(&codec, "minecraft:dimension_type", "minecraft:overworld");
+    assert!(!overworld.contains_key("fixed_time"));
+    assert_eq!(overworld["has_skylight"], Tag::Byte(1));
+    assert_eq!(overworld["min_y"], Tag::Int(-64));
+    assert_eq!(overworld["effects"].as_str(), Some("minecraft:overworld"));
+    assert_eq!(
+        overworld["monster_spawn_light_level"],
+        Tag::Compound(
+            Compound::new().with("type", "minecraft:uniform").with(
+                "value",
+                Compound::new()
+                    .with("min_inclusive", 0)
+                    .with("max_inclusive", 7)
+            )
+        )
+    );
+
+    let nether = element(&codec, "minecraft:dimension_type", "minecraft:the_nether");
+    assert_eq!(nether["fixed_time"], Tag::Long(18000));
+    assert_eq!(nether["monster_spawn_light_level"], Tag::Int(7));
+}
+
+#[test]
+fn custom_entries_are_added_to_the_codec() {
+    let void = Identifier::new("arte", "void").unwrap();
+    let registries = Registries::vanilla()
+        .with_dimension_type(
+            void.clone(),
+            DimensionType {
+                fixed_time: Some(6000),
+                has_skylight: false,
+                monster_spawn_light_level: MonsterSpawnLightLevel::Constant(0),
+                ..DimensionType::overworld()
+            },
+        )
+        .with_biome(
+            void.clone(),
+            Biome {
+                has_precipitation: false,
+                ..Biome::the_void()
+            },
+        );
+    assert_eq!(registries.dimension_types.id(&void), Some(4));
+
+    let codec = registries.codec().unwrap();
+    let dimension = element(&codec, "minecraft:dimension_type", "arte:void");
+    assert_eq!(dimension["fixed_time"], Tag::Long(6000));
+    assert_eq!(dimension["has_skylight"], Tag::Byte(0));
+    assert_eq!(dimension["monster_spawn_light_level"], Tag::Int(0));
+
+    let biome = element(&codec, "minecraft:worldgen/biome", "arte:void");
+    assert_eq!(biome["has_precipitation"], Tag::Byte(0));
+    let effects = biome["effects"].as_compound().unwrap();
+    assert_eq!(effects["sky_color"], Tag::Int(0x7ba4ff));
+    assert!(effects["mood_sound"].as_compound().is_some());
+    assert!(!effects.contains_key("music"));
+}
+
+#[test]
+fn replacing_an_entry_keeps_its_id() {
+    let mut registry = DamageType::vanilla();
+    let generic = Identifier::minecraft("generic").unwrap();
+    let id = registry.id(&generic).unwrap();
+    let len = registry.len();
+
+    let replaced = DamageType {
+        exhaustion: 1.0,
+        ..registry.get(&generic).unwrap().clone()
+    };
+    assert_eq!(registry.insert(generic.clone(), replaced), id);
+    assert_eq!(registry.len(), len);
+    assert_eq!(registry.get(&generic).unwrap().exhaustion, 1.0);
+
+    let mut registry = Registry::new(Identifier::minecraft("worldgen/biome").unwrap());
+    assert!(registry.is_empty());
+    assert_eq!(
+        registry.insert(Identifier::minecraft("a").unwrap(), Biome::plains()),
+        0
+    );
+    assert_eq!(
+        registry.insert(Identifier::minecraft("b").unwrap(), Biome::plains()),
+        1
+    );
+}
+
+#[test]
+fn chat_and_trim_entries_serialize_like_vanilla() {
+    let codec = Registries::vanilla().codec().unwrap();
+
+    let outgoing = element(
+        &codec,
+        "minecraft:chat_type",
+        "minecraft:msg_command_outgoing",
+    );
+    let chat = outgoing["chat"].as_compound().unwrap();
+    assert_eq!(
+        chat["translation_key"].as_str(),
+        Some("commands.message.display.outgoing")
+    );
+    assert_eq!(
+        chat["parameters"],
+        Tag::List(vec![Tag::from("target"), Tag::from("content")])
+    );
+    let style = chat["style"].as_compound().unwrap();
+    assert_eq!(style["color"].as_str(), Some("gray"));
+    assert_eq!(style["italic"], Tag::Byte(1));
+
+    let iron = element(&codec, "minecraft:trim_material", "minecraft:iron");
+    assert_eq!(iron["ingredient"].as_str(), Some("minecraft:iron_ingot"));
+    assert_eq!(
+        iron["override_armor_materials"],
+        Tag::Compound(Compound::new().with("iron", "iron_darker"))
+    );
+    let description = iron["description"].as_compound().unwrap();
+    assert_eq!(
+        description["translate"].as_str(),
+        Some("trim_material.minecraft.iron")
+    );
+    assert_eq!(description["color"].as_str(), Some("#ececec"));
+
+    let quartz = element(&codec, "minecraft:trim_material", "minecraft:quartz");
+    assert!(!quartz.contains_key("override_armor_materials"));
+
+    let wild = element(&codec, "minecraft:trim_pattern", "minecraft:wild");
+    assert_eq!(
+        wild["template_item"].as_str(),
+        Some("minecraft:wild_armor_trim_smithing_template")
+    );
+}
+
+#[test]
+fn codec_survives_the_network_format() {
+    let codec = Registries::vanilla().codec().unwrap();
+
+    let mut encoded = BytesMut::new();
+    codec.encode_network(&mut encoded).unwrap();
+    let decoded = Compound::decode_network(&mut encoded.freeze(), &NbtLimits::default()).unwrap();
+    assert_eq!(decoded, codec);
+}
@@ -0,0 +1,183 @@
+use std::fmt::Debug;
+
+use arte_protocol::{
+    fundamental::{
+        FrameLimits, Json, PacketData, ReadExt, SerializedPacket, StringLimit, VarInt, Zlib,
+    },
+    *,
+};
+use async_std::task;
+use bytes::{Bytes, BytesMut};
+use proptest::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Checks that both codecs write the same bytes and read back `value` from them, consuming
+/// all of it.
+fn assert_round_trip<T>(value: T)
+where
+    T: AsyncSerialize + AsyncDeserialize + Encode + Decode + PartialEq + Debug,
+{
+    task::block_on(async {
+        let mut written = vec![];
+        value.write_to(&mut written).await.unwrap();
+
+        let mut encoded = BytesMut::new();
+        value.encode(&mut encoded).unwrap();
+        assert_eq!(encoded, written);
+
+        let mut reader = &written[..];
+        let read: T = reader.deserialize().await.unwrap();
+        assert_eq!(read, value);
+        assert!(reader.is_empty());
+
+        let mut buf = encoded.freeze();
+        assert_eq!(T::decode(&mut buf).unwrap(), value);
+        assert!(buf.is_empty());
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    name: String,
+    values: Vec<i64>,
+    flag: Option<bool>,
+}
+
+fn entry() -> impl Strategy<Value = Entry> {
+    (
+        ".{0,8}",
+        prop::collection::vec(any::<i64>(), 0..4),
+        any::<Option<bool>>(),
+    )
+        .prop_map(|(name, values, flag)| Entry { name, values, flag })
+}
+
+fn packet_data() -> impl Strategy<Value = (i32, Vec<u8>)> {
+    (0..0x80i32, prop::collection::vec(any::<u8>(), 0..1024))
+}
+
+proptest! {
+    #[test]
+    fn ints(a: u8, b: i8, c: u16, d: i16, e: u32, f: i32, g: u64, h: i64) {
+        assert_round_trip(a);
+        assert_round_trip(b);
+        assert_round_trip(c);
+        assert_round_trip(d);
+        assert_round_trip(e);
+        assert_round_trip(f);
+        assert_round_trip(g);
+        assert_round_trip(h);
+    }
+
+    #[test]
+    fn bools(value: bool) {
+        assert_round_trip(value);
+    }
+
+    #[test]
+    fn varints(a: i32, b: i64, c: u32, d: u64) {
+        assert_round_trip(VarInt(a));
+        assert_round_trip(VarInt(b));
+        assert_round_trip(VarInt(c));
+        assert_round_trip(VarInt(d));
+
+        assert_eq!(VarInt(a).length(), VarInt(a as u32).length());
+        assert_eq!(VarInt(b).length(), VarInt(b as u64).length());
+    }
+
+    #[test]
+    fn strings(value in ".{0,64}") {
+        assert_round_trip(value.clone());
+        assert_round_trip(StringLimit::<256>(value));
+    }
+
+    #[test]
+    fn uuids(value: u128) {
+        assert_round_trip(Uuid::from_u128(value));
+    }
+
+    #[test]
+    fn options(value: Option<i32>, text in prop::option::of(".{0,8}")) {
+        assert_round_trip(value);
+        assert_round_trip(text);
+    }
+
+    #[test]
+    fn vecs(
+        bytes in prop::collection::vec(any::<u8>(), 0..256),
+        nested in prop::collection::vec(prop::collection::vec(any::<i16>(), 0..4), 0..8),
+    ) {
+        assert_round_trip(bytes);
+        assert_round_trip(nested);
+    }
+
+    #[test]
+    fn json(value in entry()) {
+        let mut encoded = BytesMut::new();
+        Json(&value).encode(&mut encoded).unwrap();
+
+        let read = task::block_on((&encoded[..]).deserialize::<Json<Entry>>()).unwrap();
+        assert_eq!(read.0, value);
+        assert_eq!(Json::<Entry>::decode(&mut encoded.freeze()).unwrap().0, value);
+    }
+
+    #[test]
+    fn zlib(value in prop::collection::vec(".{0,16}", 0..32)) {
+        // the compressed bytes depend on the deflate implementation, so only check that
+        // each side reads what the other wrote
+        let mut written = vec![];
+        task::block_on(Zlib(value.clone()).write_to(&mut written)).unwrap();
+        let mut encoded = BytesMut::new();
+        Zlib(value.clone()).encode(&mut encoded).unwrap();
+
+        let Zlib(decoded) = Zlib::<Vec<String>>::decode(&mut Bytes::from(written)).unwrap();
+        assert_eq!(decoded, value);
+        let Zlib(read) =
+            task::block_on((&encoded[..]).deserialize::<Zlib<Vec<String>>>()).unwrap();
+        assert_eq!(read, value);
+    }
+
+    #[test]
+    fn packet_data_round_trips((id, data) in packet_data()) {
+        let packet = PacketData { packet_id: VarInt(id), data: data.into() };
+
+        let mut written = vec![];
+        task::block_on(packet.write_to(&mut written)).unwrap();
+        let mut encoded = BytesMut::new();
+        packet.encode(&mut encoded).unwrap();
+        assert_eq!(encoded, written);
+
+        let decoded = PacketData::decode(&mut encoded.freeze()).unwrap();
+        assert_eq!(decoded.packet_id, packet.packet_id);
+        assert_eq!(decoded.data, packet.data);
+    }
+
+    #[test]
+    fn frames_round_trip(
+        (id, data) in packet_data(),
+        threshold in prop::option::of(0..512usize),
+    ) {
+        let data = PacketData { packet_id: VarInt(id), data: data.into() };
+        let packet = match threshold {
+            Some(_) => SerializedPacket::Compressed(Zlib(data)),
+            None => SerializedPacket::Uncompressed(data),
+        };
+
+        let mut frame = vec![];
+        task::block_on(packet.write_to(&mut frame, threshold)).unwrap();
+
+        let mut reader = &frame[..];
+        let read = task::block_on(SerializedPacket::read_from(
+            &mut reader,
+            threshold,
+            &FrameLimits::default(),
+        ))
+        .unwrap()
+        .into_data();
+        assert!(reader.is_empty());
+
+        let sent = packet.into_data();
+        assert_eq!(read.packet_id, sent.packet_id);
+        assert_eq!(read.data, sent.data);
+    }
+}
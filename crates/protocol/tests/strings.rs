@@ -0,0 +1,106 @@
+use std::io::ErrorKind;
+
+use arte_protocol::{
+    fundamental::{read_string_limit, StringLimit, VarInt},
+    *,
+};
+use async_std::task;
+use bytes::BytesMut;
+
+/// A length prefix followed by `bytes`, which need not be valid UTF-8.
+fn prefixed(length: i32, bytes: &[u8]) -> Vec<u8> {
+    let mut data = BytesMut::new();
+    VarInt(length).encode(&mut data).unwrap();
+    data.extend_from_slice(bytes);
+    data.to_vec()
+}
+
+/// Reads `data` as a `StringLimit<16>` with both codecs, which must agree.
+fn read_16(data: &[u8]) -> Result<String> {
+    let decoded = StringLimit::<16>::decode(&mut &data[..]).map(|string| string.0);
+    let read = task::block_on(read_string_limit(&mut &data[..], 16));
+
+    match (&decoded, &read) {
+        (Ok(decoded), Ok(read)) => assert_eq!(decoded, read),
+        // the messages of io errors differ
+        (Err(Error::Io(decoded)), Err(Error::Io(read))) => assert_eq!(decoded.kind(), read.kind()),
+        (Err(decoded), Err(read)) => assert_eq!(decoded.to_string(), read.to_string()),
+        _ => panic!("codecs disagree: {decoded:?} and {read:?}"),
+    }
+    decoded
+}
+
+fn io_kind(result: Result<String>) -> ErrorKind {
+    match result {
+        Err(Error::Io(error)) => error.kind(),
+        other => panic!("expected an io error, got {other:?}"),
+    }
+}
+
+#[test]
+fn limits_count_utf16_code_units() {
+    // two bytes each in UTF-8, but one code unit
+    let accents = "é".repeat(16);
+    assert_eq!(read_16(&prefixed(32, accents.as_bytes())).unwrap(), accents);
+
+    // four bytes and two code units each
+    let faces = "😀".repeat(8);
+    assert_eq!(read_16(&prefixed(32, faces.as_bytes())).unwrap(), faces);
+
+    let faces = "😀".repeat(9);
+    assert!(matches!(
+        read_16(&prefixed(36, faces.as_bytes())),
+        Err(Error::StringTooLarge {
+            length: 18,
+            limit: 16
+        })
+    ));
+}
+
+#[test]
+fn byte_lengths_past_three_per_unit_are_rejected_up_front() {
+    assert!(matches!(
+        read_16(&prefixed(49, &[])),
+        Err(Error::StringTooLarge {
+            length: 49,
+            limit: 48
+        })
+    ));
+}
+
+#[test]
+fn short_and_invalid_strings_are_errors() {
+    assert_eq!(
+        io_kind(read_16(&prefixed(5, b"ab"))),
+        ErrorKind::UnexpectedEof
+    );
+    assert_eq!(
+        io_kind(read_16(&prefixed(2, &[0xc3, 0x28]))),
+        ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn writing_checks_the_limit() {
+    task::block_on(async {
+        let fits = StringLimit::<2>(String::from("éé"));
+        assert!(fits.encode(&mut BytesMut::new()).is_ok());
+        assert!(fits.write_to(&mut vec![]).await.is_ok());
+
+        let too_long = StringLimit::<2>(String::from("😀a"));
+        assert!(matches!(
+            too_long.encode(&mut BytesMut::new()),
+            Err(Error::StringTooLarge {
+                length: 3,
+                limit: 2
+            })
+        ));
+        assert!(matches!(
+            too_long.write_to(&mut vec![]).await,
+            Err(Error::StringTooLarge {
+                length: 3,
+                limit: 2
+            })
+        ));
+    })
+}
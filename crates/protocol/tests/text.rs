@@ -0,0 +1,162 @@
+use arte_protocol::{
+    comptime_uuid,
+    fundamental::{Json, ReadExt, WriteExt},
+    model::{ClickEvent, Color, HoverEvent, Style, TextComponent, TextContent},
+};
+use async_std::task;
+use serde_json::json;
+
+#[test]
+fn builder_serializes_like_vanilla() {
+    let component = TextComponent::text("Hello ")
+        .color(Color::Gold)
+        .bold(true)
+        .append(
+            TextComponent::translate_with("chat.type.text", ["Notch", "hi"])
+                .color(Color::Rgb(0x12, 0xab, 0xef))
+                .click(ClickEvent::SuggestCommand(String::from("/msg Notch "))),
+        )
+        .append(TextComponent::keybind("key.inventory").italic(false));
+
+    assert_eq!(
+        serde_json::to_value(&component).unwrap(),
+        json!({
+            "text": "Hello ",
+            "color": "gold",
+            "bold": true,
+            "extra": [
+                {
+                    "translate": "chat.type.text",
+                    "with": [{ "text": "Notch" }, { "text": "hi" }],
+                    "color": "#12abef",
+                    "clickEvent": { "action": "suggest_command", "value": "/msg Notch " },
+                },
+                { "keybind": "key.inventory", "italic": false },
+            ],
+        })
+    );
+}
+
+#[test]
+fn deserializes_every_shape() {
+    let component: TextComponent = serde_json::from_value(json!("plain")).unwrap();
+    assert_eq!(component, TextComponent::text("plain"));
+
+    let component: TextComponent =
+        serde_json::from_value(json!(["a", { "text": "b", "color": "red" }])).unwrap();
+    assert_eq!(
+        component,
+        TextComponent::text("a").append(TextComponent::text("b").color(Color::Red))
+    );
+
+    let component: TextComponent = serde_json::from_value(json!({
+        "score": { "name": "@p", "objective": "kills" },
+        "underlined": true,
+        "hoverEvent": {
+            "action": "show_entity",
+            "contents": {
+                "type": "minecraft:pig",
+                "id": "069a79f4-44e9-4726-a5be-fca90e38aaf5",
+                "name": "Babe",
+            },
+        },
+    }))
+    .unwrap();
+    assert_eq!(
+        component,
+        TextComponent::score("@p", "kills")
+            .underlined(true)
+            .hover(HoverEvent::ShowEntity {
+                kind: String::from("minecraft:pig"),
+                id: comptime_uuid!("069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+                name: Some(Box::new(TextComponent::text("Babe"))),
+            })
+    );
+
+    let component: TextComponent =
+        serde_json::from_value(json!({ "selector": "@a", "separator": ", " })).unwrap();
+    assert!(matches!(
+        component.content,
+        TextContent::Selector {
+            separator: Some(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn rejects_bad_colors() {
+    assert!(
+        serde_json::from_value::<TextComponent>(json!({ "text": "", "color": "pink" })).is_err()
+    );
+    assert!(
+        serde_json::from_value::<TextComponent>(json!({ "text": "", "color": "#12345" })).is_err()
+    );
+}
+
+#[test]
+fn round_trips_through_json_packets() {
+    task::block_on(async {
+        let component = TextComponent::text("Kicked")
+            .color(Color::Red)
+            .append(TextComponent::text(" for spam").obfuscated(true));
+
+        let mut data = vec![];
+        data.serialize(&Json(&component)).await.unwrap();
+
+        let Json(read): Json<TextComponent> = (&data[..]).deserialize().await.unwrap();
+        assert_eq!(read, component);
+    })
+}
+
+#[test]
+fn converts_from_legacy() {
+    assert_eq!(
+        TextComponent::from_legacy("A Minecraft Server"),
+        TextComponent::text("A Minecraft Server")
+    );
+
+    assert_eq!(
+        TextComponent::from_legacy("§6Gold §lbold§r plain §x§1§2§a§b§e§fhex§z"),
+        TextComponent::text("")
+            .append(TextComponent::text("Gold ").color(Color::Gold))
+            .append(TextComponent::text("bold").color(Color::Gold).bold(true))
+            .append(TextComponent::text(" plain "))
+            .append(TextComponent::text("hex").color(Color::Rgb(0x12, 0xab, 0xef)))
+    );
+}
+
+#[test]
+fn converts_to_legacy() {
+    let component = TextComponent::text("Gold ")
+        .color(Color::Gold)
+        .append(TextComponent::text("bold").bold(true))
+        .append(TextComponent::text(" still gold"))
+        .append(TextComponent::text(" hex").color(Color::Rgb(0x12, 0xab, 0xef)));
+
+    assert_eq!(
+        component.to_legacy(),
+        "§6Gold §6§lbold§6 still gold§x§1§2§a§b§e§f hex"
+    );
+    assert_eq!(
+        TextComponent::from_legacy(&component.to_legacy()).to_legacy(),
+        component.to_legacy()
+    );
+}
+
+#[test]
+fn style_inherits_from_parent() {
+    let parent = Style {
+        color: Some(Color::Aqua),
+        bold: Some(true),
+        ..Style::default()
+    };
+    let child = Style {
+        bold: Some(false),
+        ..Style::default()
+    };
+
+    let style = child.inherit(&parent);
+    assert_eq!(style.color, Some(Color::Aqua));
+    assert_eq!(style.bold, Some(false));
+}
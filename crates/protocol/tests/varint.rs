@@ -0,0 +1,115 @@
+use arte_protocol::{
+    fundamental::{ReadExt, VarInt, VarLong, VarNumber},
+    *,
+};
+use async_std::task;
+use bytes::BytesMut;
+
+// from https://wiki.vg/Protocol#VarInt_and_VarLong
+const VARINTS: &[(i32, &[u8])] = &[
+    (0, &[0x00]),
+    (1, &[0x01]),
+    (2, &[0x02]),
+    (127, &[0x7f]),
+    (128, &[0x80, 0x01]),
+    (255, &[0xff, 0x01]),
+    (25565, &[0xdd, 0xc7, 0x01]),
+    (2097151, &[0xff, 0xff, 0x7f]),
+    (2147483647, &[0xff, 0xff, 0xff, 0xff, 0x07]),
+    (-1, &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+    (-2147483648, &[0x80, 0x80, 0x80, 0x80, 0x08]),
+];
+
+const VARLONGS: &[(i64, &[u8])] = &[
+    (0, &[0x00]),
+    (1, &[0x01]),
+    (2, &[0x02]),
+    (127, &[0x7f]),
+    (128, &[0x80, 0x01]),
+    (255, &[0xff, 0x01]),
+    (2147483647, &[0xff, 0xff, 0xff, 0xff, 0x07]),
+    (
+        9223372036854775807,
+        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f],
+    ),
+    (
+        -1,
+        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+    ),
+    (
+        -2147483648,
+        &[0x80, 0x80, 0x80, 0x80, 0xf8, 0xff, 0xff, 0xff, 0xff, 0x01],
+    ),
+    (
+        -9223372036854775808,
+        &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+    ),
+];
+
+fn assert_vector<T: VarNumber + PartialEq + std::fmt::Debug>(value: T, bytes: &[u8]) {
+    task::block_on(async {
+        let mut written = vec![];
+        VarInt(value).write_to(&mut written).await.unwrap();
+        assert_eq!(written, bytes, "writing {value:?}");
+
+        let mut encoded = BytesMut::new();
+        VarInt(value).encode(&mut encoded).unwrap();
+        assert_eq!(encoded, bytes, "encoding {value:?}");
+
+        assert_eq!(VarInt(value).length(), bytes.len(), "length of {value:?}");
+
+        let read: VarInt<T> = (&bytes[..]).deserialize().await.unwrap();
+        assert_eq!(read.0, value, "reading {bytes:02x?}");
+        assert_eq!(VarInt::<T>::decode(&mut &bytes[..]).unwrap().0, value);
+    })
+}
+
+#[test]
+fn varints_match_the_spec() {
+    for &(value, bytes) in VARINTS {
+        assert_vector(value, bytes);
+    }
+}
+
+#[test]
+fn varlongs_match_the_spec() {
+    for &(value, bytes) in VARLONGS {
+        assert_vector(value, bytes);
+    }
+}
+
+#[test]
+fn unsigned_views_share_the_encoding() {
+    for &(value, bytes) in VARINTS {
+        assert_vector(value as u32, bytes);
+    }
+    for &(value, bytes) in VARLONGS {
+        assert_vector(value as u64, bytes);
+    }
+}
+
+#[test]
+fn overlong_encodings_are_rejected() {
+    task::block_on(async {
+        let varint = [0x80; 6];
+        assert!(matches!(
+            (&varint[..]).deserialize::<VarInt>().await,
+            Err(Error::VarIntTooLong(5))
+        ));
+        assert!(matches!(
+            VarInt::<i32>::decode(&mut &varint[..]),
+            Err(Error::VarIntTooLong(5))
+        ));
+
+        // never ends, so reading has to give up after 10 bytes
+        let varlong = [0xff; 64];
+        assert!(matches!(
+            (&varlong[..]).deserialize::<VarLong>().await,
+            Err(Error::VarIntTooLong(10))
+        ));
+        assert!(matches!(
+            VarLong::decode(&mut &varlong[..]),
+            Err(Error::VarIntTooLong(10))
+        ));
+    })
+}
@@ -0,0 +1,140 @@
+use arte_protocol::phases::login::Property;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+
+use super::*;
+
+/// A player's identity as confirmed by a session server.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct GameProfile {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+}
+
+/// Checks that a player connecting in online mode really owns the account they claim.
+pub trait Authenticator: Send + Sync {
+    /// `server_hash` is the digest from [`server_hash`] that the client also sent to the
+    /// session server when joining.
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        server_hash: &'a str,
+    ) -> BoxFuture<'a, Result<GameProfile>>;
+}
+
+/// Minecraft's SHA-1 digest of the login, printed as a signed two's-complement number.
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key: &[u8]) -> String {
+    let mut digest: [u8; 20] = Sha1::new()
+        .chain_update(server_id)
+        .chain_update(shared_secret)
+        .chain_update(public_key)
+        .finalize()
+        .into();
+
+    let negative = digest[0] & 0x80 != 0;
+    if negative {
+        // negate the whole number: invert every bit, then add one
+        let mut carry = true;
+        for byte in digest.iter_mut().rev() {
+            let (sum, overflow) = (!*byte).overflowing_add(carry as u8);
+            *byte = sum;
+            carry = overflow;
+        }
+    }
+
+    let hex = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let hex = hex.trim_start_matches('0');
+
+    if negative {
+        format!("-{hex}")
+    } else {
+        hex.to_owned()
+    }
+}
+
+/// Asks Mojang's (or a compatible) session server whether the player has joined.
+pub struct MojangAuthenticator {
+    /// Everything before `/session/minecraft/hasJoined`.
+    pub session_url: String,
+}
+
+impl MojangAuthenticator {
+    pub const SESSION_URL: &'static str = "https://sessionserver.mojang.com";
+}
+
+impl Default for MojangAuthenticator {
+    fn default() -> Self {
+        Self {
+            session_url: String::from(Self::SESSION_URL),
+        }
+    }
+}
+
+impl Authenticator for MojangAuthenticator {
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        server_hash: &'a str,
+    ) -> BoxFuture<'a, Result<GameProfile>> {
+        let url = format!(
+            "{}/session/minecraft/hasJoined",
+            self.session_url.trim_end_matches('/')
+        );
+        let username = username.to_owned();
+        let server_hash = server_hash.to_owned();
+
+        // ureq is blocking, keep it off the executor
+        Box::pin(async_std::task::spawn_blocking(move || {
+            let response = ureq::get(&url)
+                .query("username", &username)
+                .query("serverId", &server_hash)
+                .call()
+                .map_err(|error| Error::Authentication(error.to_string()))?;
+
+            // the session server answers 204 No Content if the player never joined
+            if response.status() != 200 {
+                return Err(Error::Authentication(format!(
+                    "{username} has not joined through the session server"
+                )));
+            }
+
+            response
+                .into_json()
+                .map_err(|error| Error::Authentication(error.to_string()))
+        }))
+    }
+}
+
+/// Authenticates a fixed set of profiles without any network access, for tests and
+/// local development.
+#[derive(Default)]
+pub struct MockAuthenticator {
+    pub profiles: HashMap<String, GameProfile>,
+}
+
+impl MockAuthenticator {
+    pub fn with_profile(mut self, profile: GameProfile) -> Self {
+        self.profiles.insert(profile.name.clone(), profile);
+        self
+    }
+}
+
+impl Authenticator for MockAuthenticator {
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        _server_hash: &'a str,
+    ) -> BoxFuture<'a, Result<GameProfile>> {
+        let profile = self.profiles.get(username).cloned().ok_or_else(|| {
+            Error::Authentication(format!("{username} is not a known mock profile"))
+        });
+
+        Box::pin(async move { profile })
+    }
+}
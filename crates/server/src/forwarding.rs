@@ -0,0 +1,148 @@
+use std::net::IpAddr;
+
+use arte_protocol::fundamental::{read_string_limit, ReadExt};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::*;
+
+/// Velocity's modern forwarding, which passes the player's real identity through a
+/// login plugin message signed with a secret shared between the proxy and the server.
+///
+/// Register it on [`VelocityForwarding::CHANNEL`], and leave online mode off since the
+/// proxy already authenticated the player.
+pub struct VelocityForwarding {
+    pub secret: Vec<u8>,
+}
+
+impl VelocityForwarding {
+    pub const CHANNEL: &'static str = "velocity:player_info";
+
+    /// Forwarding without the player's chat signing key.
+    pub const MODERN_DEFAULT: u8 = 1;
+
+    const SIGNATURE_LENGTH: usize = 32;
+
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+
+    async fn forward(&self, profile: &mut LoginProfile, data: Option<Bytes>) -> Result<()> {
+        let data = data.ok_or_else(|| {
+            Error::Forwarding(String::from(
+                "the client did not answer, is it connecting through Velocity?",
+            ))
+        })?;
+
+        if data.len() < Self::SIGNATURE_LENGTH {
+            return Err(Error::Forwarding(String::from("payload is too short")));
+        }
+        let (signature, payload) = data.split_at(Self::SIGNATURE_LENGTH);
+
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac.verify_slice(signature).map_err(|_| {
+            Error::Forwarding(String::from(
+                "signature does not match, is the forwarding secret the same as Velocity's?",
+            ))
+        })?;
+
+        let mut reader = payload;
+
+        let VarInt(version) = reader.deserialize().await?;
+        if !(1..=Self::MODERN_DEFAULT as i32).contains(&version) {
+            return Err(Error::Forwarding(format!(
+                "unsupported forwarding version {version}"
+            )));
+        }
+
+        let address: IpAddr = read_string_limit(&mut reader, 255)
+            .await?
+            .parse()
+            .map_err(|error| Error::Forwarding(format!("invalid player address: {error}")))?;
+
+        profile.address.set_ip(address);
+        profile.uuid = reader.deserialize().await?;
+        profile.name = read_string_limit(&mut reader, 16).await?;
+        profile.properties = reader.deserialize().await?;
+
+        Ok(())
+    }
+}
+
+impl LoginPluginHandler for VelocityForwarding {
+    fn request(&self, _profile: &LoginProfile) -> Bytes {
+        Bytes::from_static(&[Self::MODERN_DEFAULT])
+    }
+
+    fn response<'a>(
+        &'a self,
+        profile: &'a mut LoginProfile,
+        data: Option<Bytes>,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.forward(profile, data))
+    }
+}
+
+/// The player's identity as forwarded by BungeeCord's legacy IP forwarding, which
+/// smuggles it through the handshake's address as `host\0ip\0uuid\0properties`.
+///
+/// Unlike Velocity's forwarding nothing is signed, so only enable it if the server
+/// can't be reached without going through the proxy.
+#[derive(Debug, Clone)]
+pub struct BungeeCordForwarding {
+    pub address: IpAddr,
+    pub uuid: Uuid,
+    pub properties: Vec<Property>,
+}
+
+impl BungeeCordForwarding {
+    /// Strips the forwarded fields from `handshake`, leaving just the host the client
+    /// connected to.
+    pub fn split(handshake: &mut Handshake) -> Result<Self> {
+        let mut fields = handshake.address.split('\0');
+        let host = fields.next().unwrap_or_default().to_owned();
+
+        let (Some(address), Some(uuid)) = (fields.next(), fields.next()) else {
+            return Err(Error::Forwarding(String::from(
+                "the handshake has no forwarded data, is IP forwarding enabled in BungeeCord?",
+            )));
+        };
+
+        let forwarding = Self {
+            address: address
+                .parse()
+                .map_err(|error| Error::Forwarding(format!("invalid player address: {error}")))?,
+            uuid: Uuid::parse_str(uuid)
+                .map_err(|error| Error::Forwarding(format!("invalid player UUID: {error}")))?,
+            properties: fields
+                .next()
+                .map(serde_json::from_str)
+                .transpose()?
+                .unwrap_or_default(),
+        };
+
+        handshake.address = host;
+
+        Ok(forwarding)
+    }
+
+    /// Strips whatever the proxy forwarded from `handshake`, which status pings don't
+    /// have to carry.
+    pub fn strip(handshake: &mut Handshake) {
+        if let Some(end) = handshake.address.find('\0') {
+            handshake.address.truncate(end);
+        }
+    }
+
+    pub(crate) fn apply(self, profile: &mut LoginProfile) {
+        profile.address.set_ip(self.address);
+        profile.uuid = self.uuid;
+        profile.properties = self.properties;
+    }
+}
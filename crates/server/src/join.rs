@@ -0,0 +1,201 @@
+use arte_protocol::{
+    fundamental::{EnumSet, Identifier, Position},
+    model::{Ability, Difficulty, Gamemode, OptionalGamemode},
+    nbt::{Compound, Nbt},
+    phases::play::{
+        ChangeDifficulty, ClientboundPluginMessage, LoginPlay, PlayerAbilities,
+        SetDefaultSpawnPosition, SetHeldItem, SynchronizePlayerPosition,
+    },
+    registry::Registries,
+};
+use bytes::BytesMut;
+
+use super::*;
+
+/// What players are told about the world as they join it.
+#[derive(Debug, Clone)]
+pub struct JoinSettings {
+    pub game_mode: Gamemode,
+    pub hardcore: bool,
+    pub difficulty: Difficulty,
+    pub difficulty_locked: bool,
+    /// Every dimension on the server.
+    pub dimension_names: Vec<Identifier>,
+    /// The registries sent in Login (play), which 1.20.1 clients refuse to join without.
+    pub registries: Registries,
+    /// One of the dimension types in `registries`.
+    pub dimension_type: Identifier,
+    /// The dimension players spawn in.
+    pub dimension_name: Identifier,
+    pub hashed_seed: i64,
+    pub view_distance: i32,
+    pub simulation_distance: i32,
+    pub reduced_debug_info: bool,
+    pub enable_respawn_screen: bool,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    /// The block players spawn on top of.
+    pub spawn: Position,
+    /// The yaw players spawn facing.
+    pub spawn_angle: f32,
+    /// Shown in the client's debug screen.
+    pub brand: String,
+}
+
+impl Default for JoinSettings {
+    fn default() -> Self {
+        let overworld = Identifier::minecraft("overworld").unwrap();
+
+        Self {
+            game_mode: Gamemode::Survival,
+            hardcore: false,
+            difficulty: Difficulty::default(),
+            difficulty_locked: false,
+            dimension_names: vec![overworld.clone()],
+            registries: Registries::vanilla(),
+            dimension_type: overworld.clone(),
+            dimension_name: overworld,
+            hashed_seed: 0,
+            view_distance: 10,
+            simulation_distance: 10,
+            reduced_debug_info: false,
+            enable_respawn_screen: true,
+            is_debug: false,
+            is_flat: false,
+            spawn: Position::new(0, 64, 0),
+            spawn_angle: 0.0,
+            brand: String::from("arte"),
+        }
+    }
+}
+
+/// The teleport a player has yet to confirm, during which its movement is not trusted.
+#[derive(Debug, Default)]
+pub struct Teleports {
+    next_id: i32,
+    awaiting: Option<i32>,
+}
+
+impl ServerPlayer {
+    /// Sends everything a client needs to leave the loading screen, ending with a teleport to
+    /// the spawn point.
+    ///
+    /// `registry_codec` is `settings.registries.codec()`, which is too large to build for every
+    /// player.
+    pub async fn join(&mut self, settings: &JoinSettings, registry_codec: &Compound) -> Result<()> {
+        self.sender
+            .send(LoginPlay {
+                entity_id: self.entity_id,
+                is_hardcore: settings.hardcore,
+                game_mode: settings.game_mode,
+                previous_game_mode: OptionalGamemode(None),
+                dimension_names: settings.dimension_names.clone(),
+                registry_codec: Nbt(registry_codec.clone()),
+                dimension_type: settings.dimension_type.clone(),
+                dimension_name: settings.dimension_name.clone(),
+                hashed_seed: settings.hashed_seed,
+                // ignored by the client
+                max_players: 0,
+                view_distance: settings.view_distance,
+                simulation_distance: settings.simulation_distance,
+                reduced_debug_info: settings.reduced_debug_info,
+                enable_respawn_screen: settings.enable_respawn_screen,
+                is_debug: settings.is_debug,
+                is_flat: settings.is_flat,
+                death_location: None,
+                portal_cooldown: 0,
+            })
+            .await?;
+
+        let mut brand = BytesMut::new();
+        settings.brand.encode(&mut brand)?;
+        self.sender
+            .send(ClientboundPluginMessage {
+                channel: String::from("minecraft:brand"),
+                data: brand.freeze(),
+            })
+            .await?;
+
+        self.sender
+            .send(ChangeDifficulty {
+                difficulty: settings.difficulty,
+                locked: settings.difficulty_locked,
+            })
+            .await?;
+
+        self.sender
+            .send(PlayerAbilities {
+                flags: abilities(settings.game_mode),
+                flying_speed: 0.05,
+                field_of_view_modifier: 0.1,
+            })
+            .await?;
+
+        self.sender.send(SetHeldItem { slot: 0 }).await?;
+
+        self.sender
+            .send(SetDefaultSpawnPosition {
+                location: settings.spawn,
+                angle: settings.spawn_angle,
+            })
+            .await?;
+
+        let Position { x, y, z } = settings.spawn;
+        self.teleport(
+            x as f64 + 0.5,
+            y as f64,
+            z as f64 + 0.5,
+            settings.spawn_angle,
+            0.0,
+        )
+        .await
+    }
+
+    /// Moves the player, which it has to confirm before moving on its own again.
+    pub async fn teleport(&mut self, x: f64, y: f64, z: f64, yaw: f32, pitch: f32) -> Result<()> {
+        let teleport_id = self.teleports.next_id;
+        self.teleports.next_id = teleport_id.wrapping_add(1);
+        self.teleports.awaiting = Some(teleport_id);
+
+        self.sender
+            .send(SynchronizePlayerPosition {
+                x,
+                y,
+                z,
+                yaw,
+                pitch,
+                flags: EnumSet::new(),
+                teleport_id,
+            })
+            .await
+    }
+
+    /// Whether the last teleport has not been confirmed yet.
+    pub fn is_teleporting(&self) -> bool {
+        self.teleports.awaiting.is_some()
+    }
+
+    pub(crate) fn confirm_teleport(&mut self, teleport_id: i32) {
+        // like vanilla, confirmations of older teleports are ignored
+        if self.teleports.awaiting == Some(teleport_id) {
+            self.teleports.awaiting = None;
+        }
+    }
+}
+
+/// What the client may do in `game_mode`.
+fn abilities(game_mode: Gamemode) -> EnumSet<Ability> {
+    match game_mode {
+        Gamemode::Survival | Gamemode::Adventure => EnumSet::new(),
+        Gamemode::Creative => [
+            Ability::Invulnerable,
+            Ability::AllowFlying,
+            Ability::InstantBreak,
+        ]
+        .into_iter()
+        .collect(),
+        Gamemode::Spectator => [Ability::Invulnerable, Ability::Flying, Ability::AllowFlying]
+            .into_iter()
+            .collect(),
+    }
+}
@@ -0,0 +1,68 @@
+use std::time::Instant;
+
+use arte_protocol::phases::play::ClientboundKeepAlive;
+
+use super::*;
+
+/// The keep alive a player still has to answer, and how long answering took so far.
+#[derive(Debug, Default)]
+pub struct KeepAlive {
+    pending: Option<(i64, Instant)>,
+    ping: Option<Duration>,
+}
+
+impl KeepAlive {
+    /// How often vanilla sends one, it gives the client as long to answer.
+    pub const INTERVAL: Duration = Duration::from_secs(15);
+
+    /// Returns the id of a new keep alive, unless the last one is still unanswered.
+    fn start(&mut self) -> Result<i64> {
+        if self.pending.is_some() {
+            return Err(Error::KeepAliveTimeout);
+        }
+
+        let id = rand::random();
+        self.pending = Some((id, Instant::now()));
+        Ok(id)
+    }
+
+    fn answer(&mut self, id: i64) -> Result<()> {
+        match self.pending {
+            Some((pending, sent)) if pending == id => {
+                let round_trip = sent.elapsed();
+                // smoothed like vanilla, so one slow answer doesn't throw it off
+                self.ping = Some(match self.ping {
+                    Some(ping) => (ping * 3 + round_trip) / 4,
+                    None => round_trip,
+                });
+                self.pending = None;
+                Ok(())
+            }
+            _ => Err(Error::UnexpectedKeepAlive(id)),
+        }
+    }
+}
+
+impl ServerPlayer {
+    /// The round trip time of keep alives, `None` until the client has answered one.
+    pub fn ping(&self) -> Option<Duration> {
+        self.keep_alive.ping
+    }
+
+    pub(crate) fn answer_keep_alive(&mut self, id: i64) -> Result<()> {
+        self.keep_alive.answer(id)
+    }
+
+    /// Sends a keep alive every `interval`, until one goes unanswered for that long.
+    pub(crate) async fn keep_alive(me: Arc<Mutex<Self>>, interval: Duration) -> Result<()> {
+        loop {
+            async_std::task::sleep(interval).await;
+
+            let (keep_alive_id, sender) = {
+                let mut me = me.lock().await;
+                (me.keep_alive.start()?, me.sender.clone())
+            };
+            sender.send(ClientboundKeepAlive { keep_alive_id }).await?;
+        }
+    }
+}
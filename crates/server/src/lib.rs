@@ -1,10 +1,32 @@
 #![feature(try_blocks)]
 
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
 
 use arte_protocol::{
-    fundamental::SerializedPacket,
-    phases::{handshaking::Handshake, login::LoginStart},
+    fundamental::{
+        CipherStream, DecodeBudget, FrameLimits, SerializedPacket, ServerKey, StringLimit, VarInt,
+    },
+    model::TextComponent,
+    nbt::Compound,
+    phases::{
+        handshaking::{ForwardedHandshake, Handshake},
+        login::{
+            self, EncryptionRequest, EncryptionResponse, LoginStart, LoginSuccess, Property,
+            SetCompression,
+        },
+        play::{self, ClientInformation, ServerboundPlay},
+        status::{
+            PingRequest, PongResponse, ServerStatus, StatusPlayer, StatusRequest, StatusResponse,
+        },
+    },
     *,
 };
 use async_std::{
@@ -15,84 +37,352 @@ use async_std::{
 use futures::{select_biased, FutureExt};
 use tracing::*;
 
+mod auth;
+mod forwarding;
+mod join;
+mod keep_alive;
+mod players;
+mod plugin;
+mod status;
+pub use auth::*;
+pub use forwarding::*;
+pub use join::*;
+pub use keep_alive::*;
+pub use players::*;
+pub use plugin::*;
+pub use status::*;
+
 pub struct ServerPlayer {
-    pub network: PlayerNetwork,
+    /// Queues packets for the player from any task, without locking the player.
+    pub sender: PacketSender,
+    pub entity_id: i32,
     pub name: String,
     pub uuid: Uuid,
+    /// Skin and cape textures, empty in offline mode.
+    pub properties: Vec<Property>,
+    /// Where the player is connecting from, which is not the peer address when behind a
+    /// proxy that forwards it.
+    pub address: SocketAddr,
+    /// The client's settings, once it has sent them.
+    pub information: Option<ClientInformation>,
+    pub keep_alive: KeepAlive,
+    pub teleports: Teleports,
 }
 
-async fn status(network: &mut PlayerNetwork) -> Result<()> {
-    Ok(())
+async fn status(
+    server: &Server,
+    network: &mut PlayerNetwork,
+    peer: SocketAddr,
+    handshake: &Handshake,
+) -> Result<()> {
+    network.state = State::Status;
+
+    let StatusRequest = network.recv_packet().await?;
+
+    let online = server.players.list().await;
+    let mut players = Vec::with_capacity(online.len());
+    for (_, player) in online {
+        let player = player.lock().await;
+        players.push(StatusPlayer {
+            name: player.name.clone(),
+            id: player.uuid,
+        });
+    }
+
+    let status = server.status.status(StatusContext {
+        peer,
+        handshake,
+        players: &players,
+    });
+    network.send_packet(StatusResponse { status }).await?;
+
+    let PingRequest { payload } = network.recv_packet().await?;
+    network.send_packet(PongResponse { payload }).await
 }
 
-async fn login(mut network: PlayerNetwork) -> Result<ServerPlayer> {
+async fn login(
+    server: &Server,
+    network: &mut PlayerNetwork,
+    address: SocketAddr,
+    mut handshake: Handshake,
+) -> Result<LoginProfile> {
     network.state = State::Login;
 
-    let LoginStart {
-        username: name,
-        uuid,
-    } = network.recv_packet().await?;
-    let uuid = uuid.unwrap_or_else(|| {
-        debug!(?name, "Player is in offline mode");
-        let real = format!("OfflinePlayer:{name}");
-        Uuid::new_v3(&Uuid::NAMESPACE_DNS, real.as_bytes())
-    });
+    // checked only now so that outdated clients can still see the server list
+    if handshake.protocol_version != PROTOCOL_VERSION {
+        // wait for Login Start so the client isn't reset while still sending it, but don't
+        // parse it since its layout differs between versions
+        SerializedPacket::read_from(&mut network.tcp, None, &network.frame_limits).await?;
+        return Err(Error::InvalidProtocolVersion(handshake.protocol_version));
+    }
+
+    let LoginStart { username, uuid } = network.recv_packet().await?;
+
+    let mut profile = LoginProfile {
+        uuid: uuid.unwrap_or_else(|| offline_uuid(&username)),
+        name: username,
+        properties: vec![],
+        address,
+    };
+
+    if server.bungeecord_forwarding {
+        BungeeCordForwarding::split(&mut handshake)?.apply(&mut profile);
+    }
+
+    server.login_plugins.run(network, &mut profile).await?;
+
+    if let Some(online_mode) = &server.online_mode {
+        let shared_secret = encrypt(&online_mode.key, network).await?;
+        let server_hash = server_hash("", &shared_secret, online_mode.key.public_key_der());
+
+        let GameProfile {
+            id,
+            name,
+            properties,
+        } = online_mode
+            .authenticator
+            .authenticate(&profile.name, &server_hash)
+            .await?;
+
+        profile.uuid = id;
+        profile.name = name;
+        profile.properties = properties;
+    }
+
+    debug!(?profile, "Player logged in");
+
+    if let Some(threshold) = network.compression_threshold {
+        network
+            .send_packet(SetCompression {
+                threshold: VarInt::usize(threshold),
+            })
+            .await?;
+        network.compressing = true;
+    }
+
+    network
+        .send_packet(LoginSuccess {
+            uuid: profile.uuid,
+            username: profile.name.clone(),
+            properties: profile.properties.clone(),
+        })
+        .await?;
+    network.state = State::Play;
+
+    Ok(profile)
+}
 
-    Ok(ServerPlayer {
-        network,
-        name,
-        uuid,
+/// What to tell a player who is disconnected because of `error`, `None` if the connection
+/// is already gone.
+pub fn disconnect_reason(error: &Error) -> Option<TextComponent> {
+    Some(match error {
+        Error::Io(_) => return None,
+        Error::InvalidProtocolVersion(VarInt(version)) if *version < PROTOCOL_VERSION.0 => {
+            TextComponent::text(format!("Outdated client! Please use {VERSION_NAME}"))
+        }
+        Error::InvalidProtocolVersion(_) => {
+            TextComponent::text(format!("Outdated server! I'm still on {VERSION_NAME}"))
+        }
+        Error::Authentication(_) => {
+            TextComponent::translate("multiplayer.disconnect.unverified_username")
+        }
+        Error::InvalidIdState { .. }
+        | Error::UnknownPacket { .. }
+        | Error::CollectionTooLarge { .. }
+        | Error::DecodeBudgetExceeded { .. }
+        | Error::InvalidIdentifier(_)
+        | Error::VarIntTooLong(_)
+        | Error::NegativeLength(_)
+        | Error::FrameTooLarge { .. }
+        | Error::DataTooLarge { .. }
+        | Error::BelowCompressionThreshold { .. }
+        | Error::DataLengthMismatch { .. }
+        | Error::StringTooLarge { .. }
+        | Error::BadEnumValue { .. }
+        | Error::Json(_)
+        | Error::Nbt(_)
+        | Error::NbtTooDeep(_)
+        | Error::NbtTooLarge(_)
+        | Error::Rsa(_)
+        | Error::InvalidVerifyToken
+        | Error::InvalidSharedSecret(_)
+        | Error::Forwarding(_)
+        | Error::UnexpectedPluginResponse(_) => TextComponent::text(error.to_string()),
+        Error::KeepAliveTimeout | Error::UnexpectedKeepAlive(_) => {
+            TextComponent::translate("disconnect.timeout")
+        }
+        // the writer has already given up on the connection
+        Error::SendQueueFull(_) | Error::ConnectionClosed => return None,
     })
 }
 
-async fn handshake(network: &mut PlayerNetwork) -> Result<State> {
-    let handshake: Handshake = network.recv_packet().await?;
+fn offline_uuid(name: &str) -> Uuid {
+    let real = format!("OfflinePlayer:{name}");
+    Uuid::new_v3(&Uuid::NAMESPACE_DNS, real.as_bytes())
+}
 
-    debug!(network.peer_addr = %network.tcp.peer_addr()?, ?handshake);
+/// Returns the shared secret the connection is now encrypted with.
+async fn encrypt(key: &ServerKey, network: &mut PlayerNetwork) -> Result<[u8; 16]> {
+    let verify_token: [u8; 4] = rand::random();
 
-    if handshake.protocol_version != PROTOCOL_VERSION {
-        error!(
-            "Player's protocol version was not {PROTOCOL_VERSION:?}, it was {:?}",
-            handshake.protocol_version
-        );
+    network
+        .send_packet(EncryptionRequest {
+            server_id: StringLimit(String::new()),
+            public_key: key.public_key_der().to_vec(),
+            verify_token: verify_token.to_vec(),
+        })
+        .await?;
 
-        return Err(Error::InvalidProtocolVersion(handshake.protocol_version));
+    let EncryptionResponse {
+        shared_secret,
+        verify_token: encrypted_token,
+    } = network.recv_packet().await?;
+
+    if key.decrypt(&encrypted_token)? != verify_token {
+        return Err(Error::InvalidVerifyToken);
     }
 
-    Ok(handshake.next_state)
+    let shared_secret = key.decrypt(&shared_secret)?;
+    let shared_secret: [u8; 16] = shared_secret
+        .as_slice()
+        .try_into()
+        .map_err(|_| Error::InvalidSharedSecret(shared_secret.len()))?;
+
+    network.tcp.enable_encryption(&shared_secret);
+
+    Ok(shared_secret)
+}
+
+async fn handshake(server: &Server, network: &mut PlayerNetwork) -> Result<Handshake> {
+    let handshake = if server.bungeecord_forwarding {
+        network.recv_packet::<ForwardedHandshake>().await?.0
+    } else {
+        network.recv_packet().await?
+    };
+
+    debug!(network.peer_addr = %network.tcp.get_ref().peer_addr()?, ?handshake);
+
+    Ok(handshake)
+}
+
+async fn kick(sender: &PacketSender, reason: TextComponent) -> Result<()> {
+    let sent = sender.send(play::Disconnect { reason }).await;
+
+    // the writer closes the connection after, which also stops the play loop
+    sender.close();
+    sent
 }
 
 impl ServerPlayer {
+    /// Disconnects the player, showing them `reason` once what is already queued is sent.
+    pub async fn kick(&self, reason: impl Into<TextComponent>) -> Result<()> {
+        kick(&self.sender, reason.into()).await
+    }
+
+    /// Takes a connection through handshake and login, and the player it belongs to into
+    /// the Play phase, all of which is reported through `events`.
+    ///
+    /// Returns `None` for connections that only asked for the status.
     pub async fn accept(
-        server: &mut Server,
+        server: &Server,
         tcp: TcpStream,
         addr: SocketAddr,
-        error_sender: Sender<(SocketAddr, Error)>,
+        events: Sender<PlayerEvent>,
     ) -> Result<Option<Arc<Mutex<Self>>>> {
         try {
             let mut net = PlayerNetwork {
-                tcp,
+                tcp: CipherStream::new(tcp),
                 state: State::Handshaking,
                 compressing: false,
                 compression_threshold: server.compression,
+                frame_limits: server.frame_limits,
+                decode_budget: DecodeBudget::new(server.frame_limits.decode_budget),
             };
 
-            match handshake(&mut net).await? {
+            let handshake = handshake(server, &mut net).await?;
+
+            match handshake.next_state {
                 State::Play => {
-                    let player = Arc::new(Mutex::new(login(net).await?));
+                    let profile = match login(server, &mut net, addr, handshake).await {
+                        Ok(profile) => profile,
+                        Err(error) => {
+                            if let Some(reason) = disconnect_reason(&error) {
+                                // the player is gone either way
+                                let _ = net.send_packet(login::Disconnect { reason }).await;
+                            }
+                            Err(error)?
+                        }
+                    };
+
+                    let LoginProfile {
+                        name,
+                        uuid,
+                        properties,
+                        address,
+                    } = profile;
+
+                    let (reader, sender, writer) = net.split(server.send_queue);
+                    async_std::task::spawn(async move {
+                        if let Err(error) = writer.run().await {
+                            debug!(%addr, %error, "Stopped writing to player");
+                        }
+                    });
+
+                    let mut player = ServerPlayer {
+                        sender,
+                        entity_id: server.next_entity_id(),
+                        name,
+                        uuid,
+                        properties,
+                        address,
+                        information: None,
+                        keep_alive: KeepAlive::default(),
+                        teleports: Teleports::default(),
+                    };
+                    let joined: Result<()> =
+                        try { player.join(&server.join, server.registry_codec()?).await? };
+                    if let Err(error) = joined {
+                        if let Some(reason) = disconnect_reason(&error) {
+                            // the player is gone either way
+                            let _ = kick(&player.sender, reason).await;
+                        }
+                        Err(error)?
+                    }
+
+                    let player = Arc::new(Mutex::new(player));
+                    // sent before the play loop can fail, so the server never hears about the
+                    // player leaving before it joined; nobody may be listening in tests
+                    let _ = events.send(PlayerEvent::Joined(addr, player.clone())).await;
+
+                    let keep_alive_interval = server.keep_alive_interval;
                     async_std::task::spawn({
                         let player = player.clone();
-                        let compression = server.compression;
                         async move {
-                            if let Err(e) = Self::play(player, compression).await {
-                                error_sender.send((addr, e)).await.unwrap();
+                            let played = futures::try_join!(
+                                Self::play(player.clone(), reader),
+                                Self::keep_alive(player.clone(), keep_alive_interval),
+                            );
+
+                            if let Err(e) = played {
+                                if let Some(reason) = disconnect_reason(&e) {
+                                    // waiting for room in the queue mustn't keep the player locked
+                                    let sender = player.lock().await.sender.clone();
+                                    let _ = kick(&sender, reason).await;
+                                }
+                                let _ = events.send(PlayerEvent::Left(addr, e)).await;
                             }
                         }
                     });
                     Some(player.clone())
                 }
                 State::Status => {
-                    status(&mut net).await?;
+                    let mut handshake = handshake;
+                    if server.bungeecord_forwarding {
+                        // the provider should see the host, not the player's identity
+                        BungeeCordForwarding::strip(&mut handshake);
+                    }
+
+                    status(server, &mut net, addr, &handshake).await?;
                     None
                 }
                 state => panic!("invalid state {state:?}"),
@@ -100,44 +390,166 @@ impl ServerPlayer {
         }
     }
 
-    async fn play(me: Arc<Mutex<Self>>, compression: Option<usize>) -> Result<()> {
+    async fn play(me: Arc<Mutex<Self>>, mut reader: PacketReader) -> Result<()> {
         try {
-            let tcp = me.lock_arc().await.network.tcp.clone();
             loop {
-                let packet =
-                    SerializedPacket::read_packet(&mut &tcp, compression.is_some(), State::Play)
-                        .await?;
+                let packet = match reader.recv_packet().await {
+                    Ok(packet) => packet,
+                    // most play packets aren't handled yet
+                    Err(Error::UnknownPacket { id, .. }) => {
+                        trace!(?id, "Skipping unknown packet");
+                        continue;
+                    }
+                    Err(error) => Err(error)?,
+                };
+
+                match packet {
+                    ServerboundPlay::ClientInformation(information) => {
+                        me.lock().await.information = Some(information);
+                    }
+                    ServerboundPlay::KeepAlive(play::ServerboundKeepAlive { keep_alive_id }) => {
+                        me.lock().await.answer_keep_alive(keep_alive_id)?;
+                    }
+                    ServerboundPlay::ConfirmTeleportation(confirm) => {
+                        me.lock().await.confirm_teleport(confirm.teleport_id);
+                    }
+                    ServerboundPlay::PluginMessage(_) => {
+                        trace!(?packet, "Ignoring packet");
+                    }
+                }
             }
         }
     }
 }
 
+/// What connections tell [`Server::run`] about their players.
+pub enum PlayerEvent {
+    /// The player finished logging in and is now in the Play phase.
+    Joined(SocketAddr, Arc<Mutex<ServerPlayer>>),
+    /// The player was disconnected because of the error.
+    Left(SocketAddr, Error),
+}
+
+type EventChannel = (Sender<PlayerEvent>, Receiver<PlayerEvent>);
+
 pub struct Server {
-    pub players: HashMap<SocketAddr, Arc<Mutex<ServerPlayer>>>,
-    error: (Sender<(SocketAddr, Error)>, Receiver<(SocketAddr, Error)>),
+    /// Clone this before [`Server::run`] to reach the players, e.g. for
+    /// [`Players::broadcast`].
+    pub players: Players,
+    events: EventChannel,
     pub compression: Option<usize>,
     pub tcp: TcpListener,
+    pub status: Box<dyn StatusProvider>,
+    /// Encrypt connections, `None` runs the server in offline mode.
+    pub online_mode: Option<OnlineMode>,
+    pub login_plugins: LoginPlugins,
+    /// Accept BungeeCord's legacy IP forwarding in the handshake.
+    pub bungeecord_forwarding: bool,
+    /// Bounds on the frames players may send.
+    pub frame_limits: FrameLimits,
+    /// How far players may fall behind on what is sent to them.
+    pub send_queue: SendQueueLimits,
+    /// How often players are sent a keep alive, and how long they have to answer it.
+    pub keep_alive_interval: Duration,
+    /// What players are told about the world as they join.
+    pub join: JoinSettings,
+    /// Built from `join.registries` for the first player to join.
+    registry_codec: OnceLock<Compound>,
+    entity_ids: AtomicI32,
+}
+
+pub struct OnlineMode {
+    pub key: ServerKey,
+    pub authenticator: Box<dyn Authenticator>,
+}
+
+impl OnlineMode {
+    /// Generates a fresh keypair, which takes a moment, and authenticates against Mojang.
+    pub fn new() -> Result<Self> {
+        Self::with_authenticator(MojangAuthenticator::default())
+    }
+
+    pub fn with_authenticator(authenticator: impl Authenticator + 'static) -> Result<Self> {
+        Ok(Self {
+            key: ServerKey::generate()?,
+            authenticator: Box::new(authenticator),
+        })
+    }
 }
 
 impl Server {
-    pub async fn run(&mut self) -> Result<()> {
+    pub fn new(tcp: TcpListener) -> Self {
+        Self {
+            players: Players::default(),
+            events: async_std::channel::unbounded(),
+            compression: None,
+            tcp,
+            status: Box::new(StaticStatus::default()),
+            online_mode: None,
+            login_plugins: LoginPlugins::default(),
+            bungeecord_forwarding: false,
+            frame_limits: FrameLimits::default(),
+            send_queue: SendQueueLimits::default(),
+            keep_alive_interval: KeepAlive::INTERVAL,
+            join: JoinSettings::default(),
+            registry_codec: OnceLock::new(),
+            entity_ids: AtomicI32::new(0),
+        }
+    }
+
+    /// Hands out entity ids, which have to be unique among everything the client sees.
+    pub fn next_entity_id(&self) -> i32 {
+        // wraps around on overflow
+        self.entity_ids
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_add(1)
+    }
+
+    /// The codec of [`JoinSettings::registries`], which is the same for every player.
+    fn registry_codec(&self) -> Result<&Compound> {
+        if let Some(codec) = self.registry_codec.get() {
+            return Ok(codec);
+        }
+
+        // players joining at the same time may both build it, but only one is kept
+        let codec = self.join.registries.codec()?;
+        Ok(self.registry_codec.get_or_init(|| codec))
+    }
+
+    /// Accepts connections until the listener fails, each of them in a task of its own so a
+    /// slow client or session server only holds up its own login.
+    pub async fn run(self) -> Result<()> {
+        // registries that can't be sent are a mistake to point out before anyone joins
+        self.registry_codec()?;
+        let server = Arc::new(self);
+
         loop {
             select_biased! {
-                stream = self.tcp.accept().fuse() => {
+                stream = server.tcp.accept().fuse() => {
                     let (stream, addr) = stream?;
 
-                    if let Some(player) = ServerPlayer::accept(self, stream, addr, self.error.0.clone()).await? {
-                        if self.players.insert(addr, player).is_some() {
-                            error!("Disconnecting old player with {addr} because another one joined");
+                    let server = server.clone();
+                    async_std::task::spawn(async move {
+                        let events = server.events.0.clone();
+                        if let Err(error) = ServerPlayer::accept(&server, stream, addr, events).await {
+                            error!(%addr, %error, "Error while accepting connection");
                         }
-                    }
+                    });
                 },
-                errored = self.error.1.recv().fuse() => {
-                    if let Ok((addr, error)) = errored {
-                        self.players.remove(&addr);
-                        error!(%addr, %error, "Error sent from Play phase");
-                    } else {
-                        return Ok(());
+                event = server.events.1.recv().fuse() => {
+                    // the server holds on to a sender itself, so this never fails
+                    let Ok(event) = event else { return Ok(()) };
+
+                    match event {
+                        PlayerEvent::Joined(addr, player) => {
+                            if server.players.insert(addr, player).await.is_some() {
+                                error!("Disconnecting old player with {addr} because another one joined");
+                            }
+                        }
+                        PlayerEvent::Left(addr, error) => {
+                            server.players.remove(&addr).await;
+                            error!(%addr, %error, "Error sent from Play phase");
+                        }
                     }
                 }
             }
@@ -1,2 +1 @@
 fn main() {}
-
@@ -0,0 +1,83 @@
+use async_std::sync::RwLock;
+
+use super::*;
+
+/// Everyone who is in the Play phase, keyed by the address they connected from.
+///
+/// Cloning gives another handle to the same players, for reaching them from outside
+/// [`Server::run`].
+#[derive(Clone, Default)]
+pub struct Players(Arc<RwLock<HashMap<SocketAddr, Arc<Mutex<ServerPlayer>>>>>);
+
+impl Players {
+    /// Returns the player that was connected from `addr` before, if any.
+    pub async fn insert(
+        &self,
+        addr: SocketAddr,
+        player: Arc<Mutex<ServerPlayer>>,
+    ) -> Option<Arc<Mutex<ServerPlayer>>> {
+        self.0.write().await.insert(addr, player)
+    }
+
+    pub async fn remove(&self, addr: &SocketAddr) -> Option<Arc<Mutex<ServerPlayer>>> {
+        self.0.write().await.remove(addr)
+    }
+
+    pub async fn get(&self, addr: &SocketAddr) -> Option<Arc<Mutex<ServerPlayer>>> {
+        self.0.read().await.get(addr).cloned()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.0.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.0.read().await.is_empty()
+    }
+
+    /// Every player at this moment, so none of them has to be locked while the map is.
+    pub async fn list(&self) -> Vec<(SocketAddr, Arc<Mutex<ServerPlayer>>)> {
+        self.0
+            .read()
+            .await
+            .iter()
+            .map(|(addr, player)| (*addr, player.clone()))
+            .collect()
+    }
+
+    /// Sends `packet` to every player, serializing and compressing it only once.
+    ///
+    /// Doesn't wait for players whose queue is full, they are disconnected instead.
+    pub async fn broadcast<T: Encode + Packet>(&self, packet: &T) -> Result<()> {
+        self.broadcast_filtered(packet, |_| true).await
+    }
+
+    /// Like [`Players::broadcast`], to the players `predicate` accepts.
+    pub async fn broadcast_filtered<T: Encode + Packet>(
+        &self,
+        packet: &T,
+        mut predicate: impl FnMut(&ServerPlayer) -> bool,
+    ) -> Result<()> {
+        let packet = EncodedPacket::new(packet)?;
+
+        for (addr, player) in self.list().await {
+            let sender = {
+                let player = player.lock().await;
+                if !predicate(&player) {
+                    continue;
+                }
+                player.sender.clone()
+            };
+
+            match sender.try_send_encoded(&packet) {
+                // its play loop reports it once the connection is closed
+                Err(error @ (Error::SendQueueFull(_) | Error::ConnectionClosed)) => {
+                    debug!(%addr, %error, "Couldn't broadcast to player");
+                }
+                result => result?,
+            }
+        }
+
+        Ok(())
+    }
+}
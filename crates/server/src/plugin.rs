@@ -0,0 +1,81 @@
+use arte_protocol::phases::login::{LoginPluginRequest, LoginPluginResponse};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+
+use super::*;
+
+/// Who is logging in, as far as the server knows so far.
+///
+/// Login plugins may replace any of this, e.g. with what a proxy forwarded.
+#[derive(Debug, Clone)]
+pub struct LoginProfile {
+    pub name: String,
+    pub uuid: Uuid,
+    pub properties: Vec<Property>,
+    pub address: SocketAddr,
+}
+
+/// Talks to the client (or a proxy in front of it) over one login plugin channel.
+pub trait LoginPluginHandler: Send + Sync {
+    /// The data of the Login Plugin Request sent to the client.
+    fn request(&self, profile: &LoginProfile) -> Bytes;
+
+    /// `data` is `None` if the client did not understand the channel.
+    fn response<'a>(
+        &'a self,
+        profile: &'a mut LoginProfile,
+        data: Option<Bytes>,
+    ) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Login plugin handlers keyed by their channel, all of which are queried during login.
+#[derive(Default)]
+pub struct LoginPlugins {
+    handlers: HashMap<String, Box<dyn LoginPluginHandler>>,
+}
+
+impl LoginPlugins {
+    pub fn register(
+        &mut self,
+        channel: impl Into<String>,
+        handler: impl LoginPluginHandler + 'static,
+    ) {
+        self.handlers.insert(channel.into(), Box::new(handler));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    pub(crate) async fn run(
+        &self,
+        network: &mut PlayerNetwork,
+        profile: &mut LoginProfile,
+    ) -> Result<()> {
+        let mut pending = HashMap::with_capacity(self.handlers.len());
+
+        for (message_id, (channel, handler)) in self.handlers.iter().enumerate() {
+            let message_id = VarInt::usize(message_id);
+
+            network
+                .send_packet(LoginPluginRequest {
+                    message_id,
+                    channel: channel.clone(),
+                    data: handler.request(profile),
+                })
+                .await?;
+            pending.insert(message_id, handler);
+        }
+
+        while !pending.is_empty() {
+            let LoginPluginResponse { message_id, data } = network.recv_packet().await?;
+
+            let handler = pending
+                .remove(&message_id)
+                .ok_or(Error::UnexpectedPluginResponse(message_id))?;
+            handler.response(profile, data).await?;
+        }
+
+        Ok(())
+    }
+}
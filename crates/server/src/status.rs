@@ -0,0 +1,102 @@
+use std::{io::ErrorKind, path::Path};
+
+use arte_protocol::phases::status::{StatusPlayer, StatusPlayers, StatusVersion};
+use base64::Engine;
+
+use super::*;
+
+/// Decides what a client sees in its multiplayer screen.
+///
+/// Called once per status request, so implementations can vary the response by the
+/// address the client connected from or the hostname it typed in.
+pub trait StatusProvider: Send + Sync {
+    fn status(&self, request: StatusContext<'_>) -> ServerStatus;
+}
+
+pub struct StatusContext<'a> {
+    pub peer: SocketAddr,
+    pub handshake: &'a Handshake,
+    /// Every player currently online.
+    pub players: &'a [StatusPlayer],
+}
+
+impl<F: Fn(StatusContext<'_>) -> ServerStatus + Send + Sync> StatusProvider for F {
+    fn status(&self, request: StatusContext<'_>) -> ServerStatus {
+        self(request)
+    }
+}
+
+/// The same MOTD and favicon for everyone.
+pub struct StaticStatus {
+    pub motd: String,
+    pub max_players: i32,
+    /// How many online players to list when hovering over the player count.
+    pub sample_size: usize,
+    pub favicon: Option<Favicon>,
+}
+
+impl Default for StaticStatus {
+    fn default() -> Self {
+        Self {
+            motd: String::from("An arte server"),
+            max_players: 20,
+            sample_size: 12,
+            favicon: None,
+        }
+    }
+}
+
+impl StatusProvider for StaticStatus {
+    fn status(&self, request: StatusContext<'_>) -> ServerStatus {
+        ServerStatus {
+            version: StatusVersion::default(),
+            players: Some(StatusPlayers {
+                max: self.max_players,
+                online: request.players.len().try_into().unwrap_or(i32::MAX),
+                sample: request
+                    .players
+                    .iter()
+                    .take(self.sample_size)
+                    .cloned()
+                    .collect(),
+            }),
+            description: TextComponent::from_legacy(&self.motd),
+            favicon: self.favicon.as_ref().map(|favicon| favicon.0.clone()),
+            enforces_secure_chat: false,
+        }
+    }
+}
+
+/// A server icon, already encoded as the `data:` URI the client expects.
+#[derive(Debug, Clone)]
+pub struct Favicon(pub String);
+
+impl Favicon {
+    const PNG_SIGNATURE: &'static [u8] = b"\x89PNG\r\n\x1a\n";
+
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_png(&async_std::fs::read(path.as_ref()).await?)
+    }
+
+    /// The client refuses to show anything but a 64x64 PNG image.
+    pub fn from_png(png: &[u8]) -> Result<Self> {
+        let invalid = |message| Error::Io(std::io::Error::new(ErrorKind::InvalidData, message));
+
+        if !png.starts_with(Self::PNG_SIGNATURE) {
+            return Err(invalid("favicon is not a PNG image"));
+        }
+
+        // the IHDR chunk always comes first: 4 bytes length, 4 bytes type, then width and height
+        let dimensions = png
+            .get(16..24)
+            .ok_or_else(|| invalid("favicon is missing its IHDR chunk"))?;
+        if dimensions != [0, 0, 0, 64, 0, 0, 0, 64] {
+            return Err(invalid("favicon must be 64x64 pixels"));
+        }
+
+        Ok(Self(format!(
+            "data:image/png;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(png)
+        )))
+    }
+}
@@ -0,0 +1,128 @@
+use std::net::SocketAddr;
+
+use arte_protocol::{comptime_uuid, phases::login::Property, Error};
+use arte_server::{server_hash, Authenticator, GameProfile, MojangAuthenticator};
+use async_std::{
+    io::{ReadExt, WriteExt},
+    net::TcpListener,
+    task,
+};
+
+#[test]
+fn server_hash_matches_minecraft() {
+    assert_eq!(
+        server_hash("Notch", &[], &[]),
+        "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+    );
+    assert_eq!(
+        server_hash("jeb_", &[], &[]),
+        "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+    );
+    assert_eq!(
+        server_hash("simon", &[], &[]),
+        "88e16a1019277b15d58faf0541e11910eb756f6"
+    );
+}
+
+/// Answers a single request with `response` and hands back the request line.
+async fn session_server(response: &'static str) -> (SocketAddr, task::JoinHandle<String>) {
+    let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = tcp.local_addr().unwrap();
+
+    let request = task::spawn(async move {
+        let (mut stream, _) = tcp.accept().await.unwrap();
+
+        let mut head = vec![];
+        let mut byte = [0];
+        while !head.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).await.unwrap();
+            head.push(byte[0]);
+        }
+
+        stream.write_all(response.as_bytes()).await.unwrap();
+        stream.flush().await.unwrap();
+
+        String::from_utf8(head)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .to_owned()
+    });
+
+    (addr, request)
+}
+
+#[test]
+fn mojang_authenticator_reads_profile() {
+    task::block_on(async {
+        let body = r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch","properties":[{"name":"textures","value":"e30=","signature":"c2lnbmF0dXJl"}]}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let (addr, request) = session_server(response.leak()).await;
+
+        let authenticator = MojangAuthenticator {
+            session_url: format!("http://{addr}"),
+        };
+        let profile = authenticator
+            .authenticate("Notch", "-1234abcd")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            request.await,
+            "GET /session/minecraft/hasJoined?username=Notch&serverId=-1234abcd HTTP/1.1"
+        );
+        assert_eq!(
+            profile,
+            GameProfile {
+                id: comptime_uuid!("069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+                name: String::from("Notch"),
+                properties: vec![Property {
+                    name: String::from("textures"),
+                    value: String::from("e30="),
+                    signature: Some(String::from("c2lnbmF0dXJl")),
+                }],
+            }
+        );
+    })
+}
+
+#[test]
+fn mojang_authenticator_rejects_unknown_join() {
+    task::block_on(async {
+        let (addr, _) =
+            session_server("HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").await;
+
+        let authenticator = MojangAuthenticator {
+            session_url: format!("http://{addr}"),
+        };
+
+        assert!(matches!(
+            authenticator.authenticate("Notch", "1234").await,
+            Err(Error::Authentication(_))
+        ));
+    })
+}
+
+#[test]
+fn mojang_authenticator_rejects_malformed_profile() {
+    task::block_on(async {
+        let (addr, _) = session_server(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{]",
+        )
+        .await;
+
+        let authenticator = MojangAuthenticator {
+            session_url: format!("http://{addr}"),
+        };
+
+        // so the player is told why, rather than dropped like a broken connection
+        assert!(matches!(
+            authenticator.authenticate("Notch", "1234").await,
+            Err(Error::Authentication(_))
+        ));
+    })
+}
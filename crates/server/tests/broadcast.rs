@@ -0,0 +1,49 @@
+use arte_protocol::{
+    phases::play::{ClientboundPlay, ClientboundPluginMessage},
+    *,
+};
+use async_std::task;
+use common::{join, player, start_server};
+
+mod common;
+
+async fn recv_channel(client: &mut PlayerNetwork) -> String {
+    match client.recv_packet().await.unwrap() {
+        ClientboundPlay::PluginMessage(message) => message.channel,
+        packet => panic!("expected a plugin message, got {packet:?}"),
+    }
+}
+
+fn message(channel: &str) -> ClientboundPluginMessage {
+    ClientboundPluginMessage {
+        channel: String::from(channel),
+        // over the threshold, so it's compressed for everyone at once
+        data: vec![0; 1024].into(),
+    }
+}
+
+#[test]
+fn broadcasts_reach_the_chosen_players() {
+    task::block_on(async {
+        let (addr, players) = start_server(|server| server.compression = Some(256)).await;
+
+        let mut jeb = join(addr, "jeb_").await;
+        player(&players, &jeb).await;
+        let mut notch = join(addr, "Notch").await;
+        player(&players, &notch).await;
+
+        players.broadcast(&message("test:everyone")).await.unwrap();
+        players
+            .broadcast_filtered(&message("test:notch"), |player| player.name == "Notch")
+            .await
+            .unwrap();
+        players.broadcast(&message("test:last")).await.unwrap();
+
+        assert_eq!(recv_channel(&mut jeb).await, "test:everyone");
+        assert_eq!(recv_channel(&mut jeb).await, "test:last");
+
+        assert_eq!(recv_channel(&mut notch).await, "test:everyone");
+        assert_eq!(recv_channel(&mut notch).await, "test:notch");
+        assert_eq!(recv_channel(&mut notch).await, "test:last");
+    })
+}
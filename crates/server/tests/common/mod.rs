@@ -0,0 +1,146 @@
+// every test pulls this in, but not every test uses all of it
+#![allow(dead_code)]
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use arte_protocol::{
+    fundamental::{CipherStream, DecodeBudget, FrameLimits},
+    model::TextComponent,
+    phases::{
+        handshaking::Handshake,
+        login::{self, ClientboundLogin, LoginStart, LoginSuccess, SetCompression},
+        play::ClientboundPlay,
+    },
+    *,
+};
+use arte_server::{Players, Server, ServerPlayer};
+use async_std::{
+    io::ReadExt,
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+    task,
+};
+
+/// Runs a server on a free port, once `configure` has set it up.
+pub async fn start_server(configure: impl FnOnce(&mut Server)) -> (SocketAddr, Players) {
+    let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = tcp.local_addr().unwrap();
+
+    let mut server = Server::new(tcp);
+    configure(&mut server);
+    let players = server.players.clone();
+    task::spawn(async move { server.run().await });
+
+    (addr, players)
+}
+
+/// A connection in `state` that doesn't compress or encrypt yet.
+pub fn network(tcp: TcpStream, state: State) -> PlayerNetwork {
+    PlayerNetwork {
+        tcp: CipherStream::new(tcp),
+        compressing: false,
+        state,
+        compression_threshold: None,
+        frame_limits: FrameLimits::default(),
+        decode_budget: DecodeBudget::default(),
+    }
+}
+
+/// Connects to `addr` as a current client that typed in `handshake_address`.
+pub async fn connect(
+    addr: SocketAddr,
+    handshake_address: &str,
+    next_state: State,
+) -> PlayerNetwork {
+    connect_with(
+        addr,
+        Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            address: String::from(handshake_address),
+            port: addr.port(),
+            next_state,
+        },
+    )
+    .await
+}
+
+/// Like [`connect`], with a handshake that needn't be what a current client sends.
+pub async fn connect_with(addr: SocketAddr, handshake: Handshake) -> PlayerNetwork {
+    let mut client = network(TcpStream::connect(addr).await.unwrap(), State::Handshaking);
+
+    let next_state = handshake.next_state;
+    client.send_packet(handshake).await.unwrap();
+    client.state = match next_state {
+        State::Play => State::Login,
+        state => state,
+    };
+
+    client
+}
+
+/// Asks to log in as `name`, on a connection that is in the Login phase.
+pub async fn login(client: &mut PlayerNetwork, name: &str) {
+    client
+        .send_packet(LoginStart {
+            username: String::from(name),
+            uuid: None,
+        })
+        .await
+        .unwrap();
+}
+
+/// Reads what is left of an offline login, turning on compression if the server does.
+pub async fn finish_login(client: &mut PlayerNetwork) -> LoginSuccess {
+    let success = match client.recv_packet().await.unwrap() {
+        ClientboundLogin::SetCompression(SetCompression { threshold }) => {
+            client.compressing = true;
+            client.compression_threshold = Some(threshold.0 as usize);
+            client.recv_packet().await.unwrap()
+        }
+        ClientboundLogin::LoginSuccess(success) => success,
+        packet => panic!("expected Login Success, got {packet:?}"),
+    };
+    client.state = State::Play;
+
+    success
+}
+
+/// Logs `name` in to the server at `addr`, and reads the join sequence.
+pub async fn join(addr: SocketAddr, name: &str) -> PlayerNetwork {
+    let mut client = connect(addr, "localhost", State::Play).await;
+    login(&mut client, name).await;
+    finish_login(&mut client).await;
+
+    // the join sequence ends with moving the player to spawn
+    while !matches!(
+        client.recv_packet().await.unwrap(),
+        ClientboundPlay::SynchronizePlayerPosition(_)
+    ) {}
+
+    client
+}
+
+/// The player behind `client`, once the server has added it.
+pub async fn player(players: &Players, client: &PlayerNetwork) -> Arc<Mutex<ServerPlayer>> {
+    let peer = client.tcp.get_ref().local_addr().unwrap();
+
+    // the server adds the player in its own time
+    for _ in 0..100 {
+        if let Some(player) = players.get(&peer).await {
+            return player;
+        }
+        task::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("the player never joined");
+}
+
+/// Returns the reason the server gave for disconnecting the client during login.
+pub async fn assert_disconnected(client: &mut PlayerNetwork) -> TextComponent {
+    let login::Disconnect { reason } = client.recv_packet().await.unwrap();
+
+    let mut rest = vec![];
+    client.tcp.read_to_end(&mut rest).await.unwrap();
+    assert!(rest.is_empty());
+
+    reason
+}
@@ -0,0 +1,73 @@
+use arte_protocol::{
+    fundamental::SerializedPacket,
+    model::TextComponent,
+    phases::{
+        login::{LoginSuccess, SetCompression},
+        play::{self, ClientboundPlay, LoginPlay, ServerboundKeepAlive, ServerboundPluginMessage},
+    },
+    *,
+};
+use async_std::task;
+use common::{connect, login, start_server};
+
+mod common;
+
+const THRESHOLD: usize = 256;
+
+#[test]
+fn login_switches_both_directions_to_compression() {
+    task::block_on(async {
+        let (addr, _) = start_server(|server| server.compression = Some(THRESHOLD)).await;
+        let mut client = connect(addr, "localhost", State::Play).await;
+        login(&mut client, "Notch").await;
+
+        let SetCompression { threshold } = client.recv_packet().await.unwrap();
+        assert_eq!(threshold.0 as usize, THRESHOLD);
+
+        // below the threshold, framed for compression but sent as is
+        let frame =
+            SerializedPacket::read_from(&mut client.tcp, Some(THRESHOLD), &client.frame_limits)
+                .await
+                .unwrap();
+        let SerializedPacket::Uncompressed(data) = frame else {
+            panic!("expected Login Success to be sent uncompressed");
+        };
+        assert_eq!(data.packet_id, LoginSuccess::ID);
+
+        // the registry codec is well over the threshold
+        let frame =
+            SerializedPacket::read_from(&mut client.tcp, Some(THRESHOLD), &client.frame_limits)
+                .await
+                .unwrap();
+        let SerializedPacket::Compressed(data) = frame else {
+            panic!("expected Login (play) to be compressed");
+        };
+        assert_eq!(data.0.packet_id, LoginPlay::ID);
+
+        client.state = State::Play;
+        client.compressing = true;
+        client.compression_threshold = Some(THRESHOLD);
+
+        // the server has to inflate the first to get to the second, which it then rejects
+        client
+            .send_packet(ServerboundPluginMessage {
+                channel: String::from("test:large"),
+                data: vec![0; 1024].into(),
+            })
+            .await
+            .unwrap();
+        client
+            .send_packet(ServerboundKeepAlive { keep_alive_id: 42 })
+            .await
+            .unwrap();
+
+        let reason = loop {
+            if let ClientboundPlay::Disconnect(play::Disconnect { reason }) =
+                client.recv_packet().await.unwrap()
+            {
+                break reason;
+            }
+        };
+        assert_eq!(reason, TextComponent::translate("disconnect.timeout"));
+    })
+}
@@ -0,0 +1,112 @@
+use arte_protocol::{
+    fundamental::VarInt,
+    model::TextComponent,
+    phases::{
+        handshaking::Handshake,
+        play,
+        status::{StatusRequest, StatusResponse},
+    },
+    *,
+};
+use arte_server::{KeepAlive, ServerPlayer, Teleports};
+use async_std::{
+    io::ReadExt,
+    net::{TcpListener, TcpStream},
+    task,
+};
+use common::{assert_disconnected, connect_with, login, network, start_server};
+
+mod common;
+
+async fn connect(protocol_version: VarInt, next_state: State) -> PlayerNetwork {
+    let (addr, _) = start_server(|_| {}).await;
+
+    connect_with(
+        addr,
+        Handshake {
+            protocol_version,
+            address: String::from("localhost"),
+            port: addr.port(),
+            next_state,
+        },
+    )
+    .await
+}
+
+async fn login_disconnect(protocol_version: VarInt) -> TextComponent {
+    let mut client = connect(protocol_version, State::Play).await;
+    login(&mut client, "Notch").await;
+
+    assert_disconnected(&mut client).await
+}
+
+#[test]
+fn outdated_client_is_told_so() {
+    task::block_on(async {
+        let reason = login_disconnect(VarInt(PROTOCOL_VERSION.0 - 1)).await;
+        assert_eq!(
+            reason,
+            TextComponent::text(format!("Outdated client! Please use {VERSION_NAME}"))
+        );
+    })
+}
+
+#[test]
+fn outdated_server_is_told_so() {
+    task::block_on(async {
+        let reason = login_disconnect(VarInt(PROTOCOL_VERSION.0 + 1)).await;
+        assert_eq!(
+            reason,
+            TextComponent::text(format!("Outdated server! I'm still on {VERSION_NAME}"))
+        );
+    })
+}
+
+#[test]
+fn outdated_client_still_gets_status() {
+    task::block_on(async {
+        let mut client = connect(VarInt(PROTOCOL_VERSION.0 - 1), State::Status).await;
+
+        client.send_packet(StatusRequest).await.unwrap();
+        let StatusResponse { status } = client.recv_packet().await.unwrap();
+        assert_eq!(status.version.protocol, PROTOCOL_VERSION.0);
+    })
+}
+
+#[test]
+fn kick_sends_reason_and_closes() {
+    task::block_on(async {
+        let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = tcp.local_addr().unwrap();
+
+        let mut client = network(TcpStream::connect(addr).await.unwrap(), State::Play);
+
+        let (stream, address) = tcp.accept().await.unwrap();
+        let (_reader, sender, writer) =
+            network(stream, State::Play).split(SendQueueLimits::default());
+        let writer = task::spawn(writer.run());
+
+        let player = ServerPlayer {
+            sender,
+            entity_id: 1,
+            name: String::from("Notch"),
+            uuid: Uuid::nil(),
+            properties: vec![],
+            address,
+            information: None,
+            keep_alive: KeepAlive::default(),
+            teleports: Teleports::default(),
+        };
+
+        player.kick("Bye").await.unwrap();
+
+        let play::Disconnect { reason } = client.recv_packet().await.unwrap();
+        assert_eq!(reason, TextComponent::text("Bye"));
+
+        let mut rest = vec![];
+        client.tcp.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+
+        writer.await.unwrap();
+    })
+}
@@ -0,0 +1,180 @@
+use std::net::{IpAddr, SocketAddr};
+
+use arte_protocol::{
+    fundamental::{VarInt, WriteExt},
+    phases::{
+        handshaking::Handshake,
+        login::{LoginPluginRequest, LoginPluginResponse, LoginSuccess, Property},
+    },
+    *,
+};
+use arte_server::{BungeeCordForwarding, Players, VelocityForwarding};
+use async_std::task;
+use common::{assert_disconnected, connect, login, player, start_server};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+mod common;
+
+const SECRET: &[u8] = b"hunter2";
+const UUID: Uuid = comptime_uuid!("069a79f4-44e9-4726-a5be-fca90e38aaf5");
+
+fn textures() -> Property {
+    Property {
+        name: String::from("textures"),
+        value: String::from("e30="),
+        signature: Some(String::from("c2lnbmF0dXJl")),
+    }
+}
+
+async fn start_velocity_server() -> (SocketAddr, Players) {
+    start_server(|server| {
+        server
+            .login_plugins
+            .register(VelocityForwarding::CHANNEL, VelocityForwarding::new(SECRET));
+    })
+    .await
+}
+
+async fn start_bungeecord_server() -> (SocketAddr, Players) {
+    start_server(|server| server.bungeecord_forwarding = true).await
+}
+
+async fn connect_through_proxy(addr: SocketAddr, handshake_address: &str) -> PlayerNetwork {
+    let mut client = connect(addr, handshake_address, State::Play).await;
+    login(&mut client, "proxied").await;
+    client
+}
+
+async fn velocity_payload(secret: &[u8]) -> Vec<u8> {
+    let mut payload = vec![];
+    payload
+        .serialize(&VarInt(VelocityForwarding::MODERN_DEFAULT as i32))
+        .await
+        .unwrap();
+    payload
+        .serialize(&String::from("203.0.113.7"))
+        .await
+        .unwrap();
+    payload.serialize(&UUID).await.unwrap();
+    payload.serialize(&String::from("Notch")).await.unwrap();
+    payload.serialize(&vec![textures()]).await.unwrap();
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+    mac.update(&payload);
+
+    let mut data = mac.finalize().into_bytes().to_vec();
+    data.extend(payload);
+    data
+}
+
+#[test]
+fn velocity_forwarding_overrides_profile() {
+    task::block_on(async {
+        let (addr, players) = start_velocity_server().await;
+        let mut client = connect_through_proxy(addr, "localhost").await;
+
+        let request: LoginPluginRequest = client.recv_packet().await.unwrap();
+        assert_eq!(request.channel, VelocityForwarding::CHANNEL);
+        assert_eq!(&request.data[..], [VelocityForwarding::MODERN_DEFAULT]);
+
+        client
+            .send_packet(LoginPluginResponse {
+                message_id: request.message_id,
+                data: Some(velocity_payload(SECRET).await.into()),
+            })
+            .await
+            .unwrap();
+
+        let success: LoginSuccess = client.recv_packet().await.unwrap();
+        assert_eq!(success.uuid, UUID);
+        assert_eq!(success.username, "Notch");
+        assert_eq!(success.properties, vec![textures()]);
+
+        let address = player(&players, &client).await.lock().await.address;
+        assert_eq!(address.ip(), "203.0.113.7".parse::<IpAddr>().unwrap());
+    })
+}
+
+#[test]
+fn velocity_forwarding_rejects_wrong_secret() {
+    task::block_on(async {
+        let mut client = connect_through_proxy(start_velocity_server().await.0, "localhost").await;
+
+        let request: LoginPluginRequest = client.recv_packet().await.unwrap();
+        client
+            .send_packet(LoginPluginResponse {
+                message_id: request.message_id,
+                data: Some(velocity_payload(b"wrong").await.into()),
+            })
+            .await
+            .unwrap();
+
+        assert_disconnected(&mut client).await;
+    })
+}
+
+#[test]
+fn velocity_forwarding_requires_proxy() {
+    task::block_on(async {
+        let mut client = connect_through_proxy(start_velocity_server().await.0, "localhost").await;
+
+        let request: LoginPluginRequest = client.recv_packet().await.unwrap();
+        client
+            .send_packet(LoginPluginResponse {
+                message_id: request.message_id,
+                data: None,
+            })
+            .await
+            .unwrap();
+
+        assert_disconnected(&mut client).await;
+    })
+}
+
+#[test]
+fn bungeecord_forwarding_overrides_profile() {
+    task::block_on(async {
+        let address = format!(
+            "localhost\x00203.0.113.7\x00{}\x00{}",
+            UUID.simple(),
+            serde_json::to_string(&vec![textures()]).unwrap(),
+        );
+        let (addr, players) = start_bungeecord_server().await;
+        let mut client = connect_through_proxy(addr, &address).await;
+
+        let success: LoginSuccess = client.recv_packet().await.unwrap();
+        assert_eq!(success.uuid, UUID);
+        assert_eq!(success.username, "proxied");
+        assert_eq!(success.properties, vec![textures()]);
+
+        let address = player(&players, &client).await.lock().await.address;
+        assert_eq!(address.ip(), "203.0.113.7".parse::<IpAddr>().unwrap());
+    })
+}
+
+#[test]
+fn bungeecord_forwarding_requires_proxy() {
+    task::block_on(async {
+        let mut client =
+            connect_through_proxy(start_bungeecord_server().await.0, "localhost").await;
+
+        assert_disconnected(&mut client).await;
+    })
+}
+
+#[test]
+fn bungeecord_forwarding_splits_handshake_address() {
+    let mut handshake = Handshake {
+        protocol_version: PROTOCOL_VERSION,
+        address: format!("play.example.com\x00203.0.113.7\x00{}", UUID.simple()),
+        port: 25565,
+        next_state: State::Play,
+    };
+
+    let forwarding = BungeeCordForwarding::split(&mut handshake).unwrap();
+    assert_eq!(handshake.address, "play.example.com");
+    assert_eq!(forwarding.address, "203.0.113.7".parse::<IpAddr>().unwrap());
+    assert_eq!(forwarding.uuid, UUID);
+    assert!(forwarding.properties.is_empty());
+}
@@ -0,0 +1,109 @@
+use arte_protocol::{
+    fundamental::{Identifier, Position, ReadExt},
+    model::{Ability, Difficulty, Gamemode},
+    phases::play::{ClientboundPlay, ConfirmTeleportation},
+    *,
+};
+use arte_server::JoinSettings;
+use async_std::task;
+use common::{connect, finish_login, login, player, start_server};
+
+mod common;
+
+#[test]
+fn join_sequence_reaches_the_world() {
+    task::block_on(async {
+        let (addr, players) = start_server(|server| {
+            server.join = JoinSettings {
+                game_mode: Gamemode::Creative,
+                difficulty: Difficulty::Hard,
+                spawn: Position::new(10, 70, -20),
+                spawn_angle: 90.0,
+                ..JoinSettings::default()
+            };
+        })
+        .await;
+
+        let mut client = connect(addr, "localhost", State::Play).await;
+        login(&mut client, "Notch").await;
+        finish_login(&mut client).await;
+
+        let ClientboundPlay::LoginPlay(login) = client.recv_packet().await.unwrap() else {
+            panic!("expected Login (play) first");
+        };
+        let player = player(&players, &client).await;
+        assert_eq!(login.entity_id, player.lock().await.entity_id);
+        assert_eq!(login.game_mode, Gamemode::Creative);
+        assert_eq!(
+            login.dimension_name,
+            Identifier::minecraft("overworld").unwrap()
+        );
+        assert!(login
+            .registry_codec
+            .0
+            .contains_key("minecraft:dimension_type"));
+
+        let ClientboundPlay::PluginMessage(brand) = client.recv_packet().await.unwrap() else {
+            panic!("expected the brand");
+        };
+        assert_eq!(brand.channel, "minecraft:brand");
+        let brand: String = (&brand.data[..]).deserialize().await.unwrap();
+        assert_eq!(brand, "arte");
+
+        let ClientboundPlay::ChangeDifficulty(difficulty) = client.recv_packet().await.unwrap()
+        else {
+            panic!("expected the difficulty");
+        };
+        assert_eq!(difficulty.difficulty, Difficulty::Hard);
+
+        let ClientboundPlay::PlayerAbilities(abilities) = client.recv_packet().await.unwrap()
+        else {
+            panic!("expected the abilities");
+        };
+        assert!(abilities.flags.contains(Ability::AllowFlying));
+        assert!(!abilities.flags.contains(Ability::Flying));
+
+        let ClientboundPlay::SetHeldItem(held) = client.recv_packet().await.unwrap() else {
+            panic!("expected the held item");
+        };
+        assert_eq!(held.slot, 0);
+
+        let ClientboundPlay::SetDefaultSpawnPosition(spawn) = client.recv_packet().await.unwrap()
+        else {
+            panic!("expected the spawn position");
+        };
+        assert_eq!(spawn.location, Position::new(10, 70, -20));
+
+        let ClientboundPlay::SynchronizePlayerPosition(position) =
+            client.recv_packet().await.unwrap()
+        else {
+            panic!("expected the player's position");
+        };
+        assert_eq!((position.x, position.y, position.z), (10.5, 70.0, -19.5));
+        assert_eq!(position.yaw, 90.0);
+        assert!(player.lock().await.is_teleporting());
+
+        // an outdated confirmation is ignored rather than disconnecting
+        client
+            .send_packet(ConfirmTeleportation {
+                teleport_id: position.teleport_id.wrapping_sub(1),
+            })
+            .await
+            .unwrap();
+        client
+            .send_packet(ConfirmTeleportation {
+                teleport_id: position.teleport_id,
+            })
+            .await
+            .unwrap();
+
+        // the play loop handles them in its own time
+        for _ in 0..100 {
+            if !player.lock().await.is_teleporting() {
+                return;
+            }
+            task::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("the teleport was never confirmed");
+    })
+}
@@ -0,0 +1,96 @@
+use std::{sync::Arc, time::Duration};
+
+use arte_protocol::{
+    model::TextComponent,
+    phases::play::{ClientboundPlay, ServerboundKeepAlive},
+    *,
+};
+use arte_server::ServerPlayer;
+use async_std::{io::ReadExt, sync::Mutex, task};
+use common::{player, start_server};
+
+mod common;
+
+const INTERVAL: Duration = Duration::from_millis(100);
+
+/// Logs a player in through a server sending keep alives every [`INTERVAL`].
+async fn join() -> (Arc<Mutex<ServerPlayer>>, PlayerNetwork) {
+    let (addr, players) = start_server(|server| server.keep_alive_interval = INTERVAL).await;
+    let client = common::join(addr, "Notch").await;
+
+    (player(&players, &client).await, client)
+}
+
+async fn recv_keep_alive(client: &mut PlayerNetwork) -> i64 {
+    match client.recv_packet().await.unwrap() {
+        ClientboundPlay::KeepAlive(keep_alive) => keep_alive.keep_alive_id,
+        packet => panic!("expected a keep alive, got {packet:?}"),
+    }
+}
+
+async fn assert_timed_out(client: &mut PlayerNetwork) {
+    match client.recv_packet().await.unwrap() {
+        ClientboundPlay::Disconnect(disconnect) => assert_eq!(
+            disconnect.reason,
+            TextComponent::translate("disconnect.timeout")
+        ),
+        packet => panic!("expected a disconnect, got {packet:?}"),
+    }
+
+    let mut rest = vec![];
+    client.tcp.read_to_end(&mut rest).await.unwrap();
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn answered_keep_alives_measure_ping() {
+    task::block_on(async {
+        let (player, mut client) = join().await;
+        assert_eq!(player.lock().await.ping(), None);
+
+        let mut ids = vec![];
+        for _ in 0..3 {
+            let keep_alive_id = recv_keep_alive(&mut client).await;
+            ids.push(keep_alive_id);
+            client
+                .send_packet(ServerboundKeepAlive { keep_alive_id })
+                .await
+                .unwrap();
+        }
+
+        // random, so a client can't answer ahead of time
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+
+        // still connected well after the first interval
+        recv_keep_alive(&mut client).await;
+        let ping = player.lock().await.ping().unwrap();
+        assert!(ping < INTERVAL, "{ping:?}");
+    })
+}
+
+#[test]
+fn unanswered_keep_alives_time_out() {
+    task::block_on(async {
+        let (_player, mut client) = join().await;
+
+        recv_keep_alive(&mut client).await;
+        assert_timed_out(&mut client).await;
+    })
+}
+
+#[test]
+fn wrong_keep_alive_ids_time_out() {
+    task::block_on(async {
+        let (_player, mut client) = join().await;
+
+        let keep_alive_id = recv_keep_alive(&mut client).await;
+        client
+            .send_packet(ServerboundKeepAlive {
+                keep_alive_id: keep_alive_id.wrapping_add(1),
+            })
+            .await
+            .unwrap();
+        assert_timed_out(&mut client).await;
+    })
+}
@@ -0,0 +1,169 @@
+use std::net::SocketAddr;
+
+use arte_protocol::{
+    fundamental::{CipherStream, VarInt},
+    model::TextComponent,
+    phases::login::{
+        EncryptionRequest, EncryptionResponse, LoginSuccess, Property, SetCompression,
+    },
+    *,
+};
+use arte_server::{GameProfile, MockAuthenticator, OnlineMode};
+use async_std::{
+    io::{ReadExt, WriteExt},
+    task,
+};
+use common::{assert_disconnected, connect, login, start_server};
+use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Encrypt, RsaPublicKey};
+
+mod common;
+
+fn textures() -> Property {
+    Property {
+        name: String::from("textures"),
+        value: String::from("e30="),
+        signature: Some(String::from("c2lnbmF0dXJl")),
+    }
+}
+
+async fn start_online_server() -> SocketAddr {
+    let (addr, _) = start_server(|server| {
+        server.compression = Some(256);
+        server.online_mode = Some(
+            OnlineMode::with_authenticator(MockAuthenticator::default().with_profile(
+                GameProfile {
+                    id: comptime_uuid!("069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+                    name: String::from("Notch"),
+                    properties: vec![textures()],
+                },
+            ))
+            .unwrap(),
+        );
+    })
+    .await;
+
+    addr
+}
+
+async fn start_login(addr: SocketAddr, username: &str) -> (PlayerNetwork, EncryptionRequest) {
+    let mut client = connect(addr, "localhost", State::Play).await;
+    login(&mut client, username).await;
+
+    let request = client.recv_packet().await.unwrap();
+    (client, request)
+}
+
+async fn respond(client: &mut PlayerNetwork, request: &EncryptionRequest) {
+    let key = RsaPublicKey::from_public_key_der(&request.public_key).unwrap();
+    let shared_secret: [u8; 16] = rand::random();
+    let mut rng = rand::thread_rng();
+
+    client
+        .send_packet(EncryptionResponse {
+            shared_secret: key
+                .encrypt(&mut rng, Pkcs1v15Encrypt, &shared_secret)
+                .unwrap(),
+            verify_token: key
+                .encrypt(&mut rng, Pkcs1v15Encrypt, &request.verify_token)
+                .unwrap(),
+        })
+        .await
+        .unwrap();
+    client.tcp.enable_encryption(&shared_secret);
+}
+
+#[test]
+fn online_login_succeeds() {
+    task::block_on(async {
+        let (mut client, request) = start_login(start_online_server().await, "Notch").await;
+        assert!(request.server_id.is_empty());
+
+        respond(&mut client, &request).await;
+
+        let SetCompression { threshold } = client.recv_packet().await.unwrap();
+        assert_eq!(threshold, VarInt(256));
+        client.compressing = true;
+
+        let success: LoginSuccess = client.recv_packet().await.unwrap();
+        assert_eq!(
+            success.uuid,
+            comptime_uuid!("069a79f4-44e9-4726-a5be-fca90e38aaf5")
+        );
+        assert_eq!(success.username, "Notch");
+        assert_eq!(success.properties, vec![textures()]);
+    })
+}
+
+#[test]
+fn stalled_login_does_not_hold_up_others() {
+    task::block_on(async {
+        let addr = start_online_server().await;
+
+        // never answers the encryption request
+        let (_stalled, _) = start_login(addr, "jeb_").await;
+
+        let (mut client, request) = start_login(addr, "Notch").await;
+        respond(&mut client, &request).await;
+        let SetCompression { .. } = client.recv_packet().await.unwrap();
+        client.compressing = true;
+        let success: LoginSuccess = client.recv_packet().await.unwrap();
+        assert_eq!(success.username, "Notch");
+    })
+}
+
+#[test]
+fn unauthenticated_player_is_disconnected() {
+    task::block_on(async {
+        let (mut client, request) = start_login(start_online_server().await, "jeb_").await;
+
+        respond(&mut client, &request).await;
+
+        let reason = assert_disconnected(&mut client).await;
+        assert_eq!(
+            reason,
+            TextComponent::translate("multiplayer.disconnect.unverified_username")
+        );
+    })
+}
+
+#[test]
+fn wrong_verify_token_closes_connection() {
+    task::block_on(async {
+        let (mut client, request) = start_login(start_online_server().await, "Notch").await;
+
+        let key = RsaPublicKey::from_public_key_der(&request.public_key).unwrap();
+        let mut rng = rand::thread_rng();
+
+        client
+            .send_packet(EncryptionResponse {
+                shared_secret: key.encrypt(&mut rng, Pkcs1v15Encrypt, &[0; 16]).unwrap(),
+                verify_token: key.encrypt(&mut rng, Pkcs1v15Encrypt, b"nope").unwrap(),
+            })
+            .await
+            .unwrap();
+
+        assert_disconnected(&mut client).await;
+    })
+}
+
+#[test]
+fn cipher_stream_round_trip() {
+    task::block_on(async {
+        let secret = [7; 16];
+        let message = b"the quick brown fox jumps over the lazy dog";
+
+        let mut writer = CipherStream::new(vec![]);
+        writer.enable_encryption(&secret);
+        writer.write_all(message).await.unwrap();
+        writer.flush().await.unwrap();
+
+        let encrypted = writer.into_inner();
+        assert_ne!(&encrypted[..], &message[..]);
+
+        let mut reader = CipherStream::new(&encrypted[..]);
+        reader.enable_encryption(&secret);
+        let mut decrypted = vec![];
+        reader.read_to_end(&mut decrypted).await.unwrap();
+        assert_eq!(&decrypted[..], &message[..]);
+    })
+}
@@ -0,0 +1,139 @@
+use std::sync::{Arc, Mutex};
+
+use arte_protocol::{
+    model::TextComponent,
+    phases::status::{
+        PingRequest, PongResponse, ServerStatus, StatusPlayer, StatusPlayers, StatusRequest,
+        StatusResponse, StatusVersion,
+    },
+    *,
+};
+use arte_server::{Favicon, StatusContext};
+use async_std::task;
+use common::{connect, start_server};
+use serde_json::json;
+
+mod common;
+
+#[test]
+fn server_list_ping_round_trip() {
+    task::block_on(async {
+        let (addr, _) = start_server(|_| {}).await;
+        let mut client = connect(addr, "localhost", State::Status).await;
+
+        client.send_packet(StatusRequest).await.unwrap();
+        let StatusResponse { status } = client.recv_packet().await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&status).unwrap(),
+            json!({
+                "version": { "name": VERSION_NAME, "protocol": PROTOCOL_VERSION.0 },
+                "players": { "max": 20, "online": 0 },
+                "description": { "text": "An arte server" },
+                "enforcesSecureChat": false,
+            })
+        );
+
+        let payload = 0x0123_4567_89ab_cdef;
+        client.send_packet(PingRequest { payload }).await.unwrap();
+        let pong: PongResponse = client.recv_packet().await.unwrap();
+        assert_eq!(pong.payload, payload);
+    })
+}
+
+#[test]
+fn custom_provider_sees_the_request() {
+    task::block_on(async {
+        let seen = Arc::new(Mutex::new(None));
+        let (addr, _) = start_server(|server| {
+            let seen = seen.clone();
+            server.status = Box::new(move |request: StatusContext<'_>| {
+                *seen.lock().unwrap() = Some((request.peer, request.handshake.address.clone()));
+
+                ServerStatus {
+                    version: StatusVersion::default(),
+                    players: Some(StatusPlayers {
+                        max: 1,
+                        online: 1,
+                        sample: vec![StatusPlayer {
+                            name: String::from("Notch"),
+                            id: Uuid::nil(),
+                        }],
+                    }),
+                    description: TextComponent::text(format!(
+                        "Hello {}",
+                        request.handshake.address
+                    )),
+                    favicon: None,
+                    enforces_secure_chat: false,
+                }
+            });
+        })
+        .await;
+
+        let mut client = connect(addr, "play.example.com", State::Status).await;
+        let peer = client.tcp.get_ref().local_addr().unwrap();
+
+        client.send_packet(StatusRequest).await.unwrap();
+        let StatusResponse { status } = client.recv_packet().await.unwrap();
+        assert_eq!(
+            status.description,
+            TextComponent::text("Hello play.example.com")
+        );
+        let sample = status.players.unwrap().sample;
+        assert_eq!(sample.len(), 1);
+        assert_eq!(sample[0].name, "Notch");
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            Some((peer, String::from("play.example.com")))
+        );
+    })
+}
+
+#[test]
+fn forwarded_fields_are_hidden_from_the_provider() {
+    task::block_on(async {
+        let (addr, _) = start_server(|server| {
+            server.bungeecord_forwarding = true;
+            server.status = Box::new(|request: StatusContext<'_>| ServerStatus {
+                version: StatusVersion::default(),
+                players: None,
+                description: TextComponent::text(request.handshake.address.clone()),
+                favicon: None,
+                enforces_secure_chat: false,
+            });
+        })
+        .await;
+
+        let address = format!(
+            "play.example.com\x00203.0.113.7\x00{}",
+            Uuid::nil().simple()
+        );
+        let mut client = connect(addr, &address, State::Status).await;
+
+        client.send_packet(StatusRequest).await.unwrap();
+        let StatusResponse { status } = client.recv_packet().await.unwrap();
+        assert_eq!(status.description, TextComponent::text("play.example.com"));
+    })
+}
+
+/// The signature and IHDR chunk of a `width`x`height` PNG image, which is all the favicon
+/// checks look at.
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    png.extend(width.to_be_bytes());
+    png.extend(height.to_be_bytes());
+    png.extend([8, 6, 0, 0, 0]);
+    png
+}
+
+#[test]
+fn favicon_must_be_a_64x64_png() {
+    let favicon = Favicon::from_png(&png(64, 64)).unwrap();
+    assert!(favicon.0.starts_with("data:image/png;base64,iVBORw0KGgo"));
+
+    assert!(Favicon::from_png(b"GIF89a").is_err());
+    assert!(Favicon::from_png(&png(64, 64)[..20]).is_err());
+    assert!(Favicon::from_png(&png(128, 128)).is_err());
+    assert!(Favicon::from_png(&png(64, 32)).is_err());
+}